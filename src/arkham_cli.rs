@@ -0,0 +1,272 @@
+/*
+ CLI DEFINITION : commands, flags & arguments (clap derive)
+*/
+use arkham::arkham_config::{BackupBackend, BuildSystem};
+use arkham::arkham_constants::ARKHAM_VER;
+use arkham::arkham_init::InitOptions;
+
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "arkham",
+    version = ARKHAM_VER,
+    about = "Arkham Versioning Protocol: versions, logs & snapshots for your builds",
+    disable_help_subcommand = true,
+    after_help = "Run 'arkham help' for the full guide, 'arkham help <TOPIC>' for init, version, workspace, git & hooks."
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Only print results & errors (no banners)
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print extra detail about what arkham is doing
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Trace external commands & state file reads / writes to stderr (or ARKHAM_DEBUG=1)
+    #[arg(long, global = true)]
+    pub debug: bool,
+
+    /// Append the trace to FILE as well (implies --debug, or ARKHAM_TRACE_FILE)
+    #[arg(long, global = true, value_name = "FILE")]
+    pub trace_file: Option<PathBuf>,
+
+    /// Plain output, no colors or boxes (NO_COLOR is honoured too)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Run as if arkham was started in DIR
+    #[arg(short = 'C', long, global = true, value_name = "DIR")]
+    pub project_dir: Option<PathBuf>,
+
+    /// Workspace component to work on
+    #[arg(short = 'p', long, global = true, value_name = "COMPONENT")]
+    pub component: Option<String>,
+
+    /// Fail instead of waiting when another arkham holds the project lock
+    #[arg(long, global = true)]
+    pub no_wait: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Display the general help, or help for a topic
+    Help {
+        /// init | version | workspace | git | hooks, or any command
+        topic: Option<String>,
+    },
+    /// Setup Arkham Versioning for the project
+    Init(InitArgs),
+    /// Build the project, bump & stamp the version
+    Build {
+        /// Show the build command & every file the bump would touch, change nothing
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Build even when nothing changed since the last build
+        #[arg(short, long)]
+        force: bool,
+
+        /// Passed through to the build system (e.g. V=1 -j8)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        build_args: Vec<String>,
+    },
+    /// Rebuild on every change to the project tree, no version bump
+    Watch {
+        /// Quiet time after the last change before building, in milliseconds
+        #[arg(long, value_name = "MS", default_value_t = 300)]
+        debounce: u64,
+
+        /// Passed through to the build system (e.g. V=1 -j8)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        build_args: Vec<String>,
+    },
+    /// Clean up the project
+    Clean {
+        /// Show the clean command, run nothing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Save the current project state (Git or tarball snapshot)
+    Backup {
+        /// Show the commands, snapshot & log row, change nothing
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Backend for this backup, overrides backup_backend in the config
+        #[arg(long, value_name = "git|tarball", value_parser = BackupBackend::parse)]
+        backend: Option<BackupBackend>,
+
+        /// Type the log message at the prompt instead of opening $VISUAL / $EDITOR
+        #[arg(long)]
+        no_edit: bool,
+    },
+    /// Revert project files to a saved version state
+    Restore { version: String },
+    /// Compare a saved version with another or the working tree
+    Diff {
+        #[arg(required = true, num_args = 1..=2)]
+        versions: Vec<String>,
+    },
+    /// Display all version logs
+    Archives,
+    /// Display logs for specific versions
+    ArchiveEntry {
+        #[arg(required = true)]
+        versions: Vec<String>,
+    },
+    /// Browse, search, diff, run & restore versions in a terminal UI
+    Tui,
+    /// Check a build's ed25519 signature & its embedded version
+    Verify {
+        /// Build to check (a symlink is followed to the stamped build)
+        binary: PathBuf,
+
+        /// Public key PEM (default: verify_key, else signing_key in the config)
+        #[arg(long, value_name = "PEM")]
+        key: Option<PathBuf>,
+
+        /// Detached signature (default: <build>.sig next to the build)
+        #[arg(long, value_name = "SIG")]
+        signature: Option<PathBuf>,
+
+        /// Version the stamp must carry (default: the one in the build's name)
+        #[arg(long, value_name = "X.YY")]
+        expect_version: Option<String>,
+    },
+    /// Display current app information
+    AppStatus,
+    /// Build time, size & warning trends across logged versions
+    Stats {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check version info, logs, symlinks & prev_builds for problems
+    Doctor,
+    /// Re-quote or quarantine malformed version log rows
+    Repair,
+    /// Display every component of a workspace
+    Workspace,
+    /// Fix project root & symlinks after moving the project
+    Relocate {
+        /// Root the project was moved from (default: the one in .version.info)
+        #[arg(long, value_name = "OLD_ROOT")]
+        from: Option<String>,
+    },
+}
+
+impl Command {
+    pub fn dry_run(&self) -> bool {
+        matches!(
+            self,
+            Command::Build { dry_run: true, .. }
+                | Command::Clean { dry_run: true }
+                | Command::Backup { dry_run: true, .. }
+        )
+    }
+
+    // Changes the project state : runs under the project lock, after crash recovery & schema upgrades
+    pub fn writes_state(&self) -> bool {
+        !self.dry_run()
+            && matches!(
+                self,
+                Command::Build { .. }
+                    | Command::Backup { .. }
+                    | Command::Restore { .. }
+                    | Command::Relocate { .. }
+                    | Command::Repair
+            )
+    }
+
+    // Name as typed on the command line (lock notes, per-command checks)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Help { .. } => "help",
+            Command::Init(_) => "init",
+            Command::Build { .. } => "build",
+            Command::Watch { .. } => "watch",
+            Command::Clean { .. } => "clean",
+            Command::Backup { .. } => "backup",
+            Command::Restore { .. } => "restore",
+            Command::Diff { .. } => "diff",
+            Command::Archives => "archives",
+            Command::ArchiveEntry { .. } => "archive-entry",
+            Command::Tui => "tui",
+            Command::Verify { .. } => "verify",
+            Command::AppStatus => "app-status",
+            Command::Stats { .. } => "stats",
+            Command::Doctor => "doctor",
+            Command::Repair => "repair",
+            Command::Workspace => "workspace",
+            Command::Relocate { .. } => "relocate",
+        }
+    }
+}
+
+// Anything left out is asked for (or detected) by arkham init
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Executable produced by the build
+    #[arg(long, value_name = "EXECUTABLE")]
+    pub name: Option<String>,
+
+    /// Starting version (X.YY)
+    #[arg(long, value_name = "X.YY")]
+    pub version: Option<String>,
+
+    /// Project root (default: nearest dir with a build file)
+    #[arg(long, value_name = "DIR")]
+    pub root: Option<String>,
+
+    /// make | cmake | cargo (default: detected)
+    #[arg(long, value_name = "SYSTEM", value_parser = BuildSystem::parse)]
+    pub build_system: Option<BuildSystem>,
+
+    /// Backup backend: git | tarball (default: the configured one, else git)
+    #[arg(long, value_name = "BACKEND", value_parser = BackupBackend::parse, conflicts_with = "no_config")]
+    pub backend: Option<BackupBackend>,
+
+    /// Extra build output versioned with the executable (repeatable)
+    #[arg(long = "artifact", value_name = "PATH")]
+    pub artifacts: Vec<String>,
+
+    /// Write the config file
+    #[arg(long, conflicts_with = "no_config")]
+    pub config: bool,
+
+    /// Don't write the config file
+    #[arg(long)]
+    pub no_config: bool,
+
+    /// Overwrite an existing .version.info
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+impl From<InitArgs> for InitOptions {
+    fn from(args: InitArgs) -> Self {
+        let write_config = match (args.config, args.no_config) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        };
+
+        InitOptions {
+            project_name: args.name,
+            version: args.version,
+            project_root: args.root,
+            build_system: args.build_system,
+            backup_backend: args.backend,
+            extra_artifacts: args.artifacts,
+            write_config,
+            force: args.force,
+            context: None,
+        }
+    }
+}
@@ -0,0 +1,477 @@
+/*
+ PROJECT CONFIGURATION : .arkham.conf (optional, key=value per line)
+*/
+use crate::arkham_constants::CONFIG_FILE;
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_fs::write_atomic;
+use crate::arkham_trace::trace_read;
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::Command;
+
+// Where `backup` stores the project state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackupBackend {
+    Git,
+    Tarball,
+}
+
+impl BackupBackend {
+    pub fn parse(value: &str) -> Result<Self, ArkhamError> {
+        match value.trim().to_lowercase().as_str() {
+            "git" => Ok(BackupBackend::Git),
+            "tarball" | "tar" => Ok(BackupBackend::Tarball),
+            other => Err(ArkhamError::ConfigError(format!(
+                "Unknown backup backend '{}' (expected: git | tarball)",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackupBackend::Git => "git",
+            BackupBackend::Tarball => "tarball",
+        }
+    }
+}
+
+// What `build` / `clean` drive
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum BuildSystem {
+    Make,
+    CMake,
+    Cargo,
+}
+
+impl BuildSystem {
+    pub fn parse(value: &str) -> Result<Self, ArkhamError> {
+        match value.trim().to_lowercase().as_str() {
+            "make" => Ok(BuildSystem::Make),
+            "cmake" => Ok(BuildSystem::CMake),
+            "cargo" => Ok(BuildSystem::Cargo),
+            other => Err(ArkhamError::ConfigError(format!(
+                "Unknown build system '{}' (expected: make | cmake | cargo)",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildSystem::Make => "make",
+            BuildSystem::CMake => "cmake",
+            BuildSystem::Cargo => "cargo",
+        }
+    }
+
+    // Marker file => build system (Makefile wins, it's what arkham always ran)
+    pub fn detect(dir: &Path) -> Option<Self> {
+        if ["Makefile", "makefile", "GNUmakefile"]
+            .iter()
+            .any(|file| dir.join(file).exists())
+        {
+            Some(BuildSystem::Make)
+        } else if dir.join("CMakeLists.txt").exists() {
+            Some(BuildSystem::CMake)
+        } else if dir.join("Cargo.toml").exists() {
+            Some(BuildSystem::Cargo)
+        } else {
+            None
+        }
+    }
+
+    // Where a plain build leaves the executable : make in the root, cmake in build/, cargo in target/debug/
+    pub fn default_artifact(&self, project_name: &str) -> String {
+        match self {
+            BuildSystem::Make => project_name.to_string(),
+            BuildSystem::CMake => format!("build/{}", project_name),
+            BuildSystem::Cargo => format!("target/debug/{}", project_name),
+        }
+    }
+
+    // Build command + user's extra args
+    pub fn build_command(&self, args: &[String]) -> Command {
+        let mut cmd = match self {
+            BuildSystem::Make => Command::new("make"),
+            BuildSystem::CMake => {
+                let mut cmd = Command::new("cmake");
+                cmd.args(["--build", "build"]);
+                cmd
+            }
+            BuildSystem::Cargo => {
+                let mut cmd = Command::new("cargo");
+                cmd.arg("build");
+                cmd
+            }
+        };
+        cmd.args(args);
+        cmd
+    }
+
+    // Workspace component's target
+    pub fn target_args(&self, target: &str) -> Vec<String> {
+        match self {
+            BuildSystem::Make => vec![target.to_string()],
+            BuildSystem::CMake => vec!["--target".to_string(), target.to_string()],
+            BuildSystem::Cargo => vec!["-p".to_string(), target.to_string()],
+        }
+    }
+
+    pub fn clean_command(&self) -> Command {
+        match self {
+            BuildSystem::Make => {
+                let mut cmd = Command::new("make");
+                cmd.arg("clean");
+                cmd
+            }
+            BuildSystem::CMake => {
+                let mut cmd = Command::new("cmake");
+                cmd.args(["--build", "build", "--target", "clean"]);
+                cmd
+            }
+            BuildSystem::Cargo => {
+                let mut cmd = Command::new("cargo");
+                cmd.arg("clean");
+                cmd
+            }
+        }
+    }
+}
+
+// Lifecycle stages a hook.<stage>=<command> line can attach to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hook {
+    PreBuild,
+    PostBuild,
+    PreBump,
+    PostBump,
+    PreBackup,
+    PostBackup,
+}
+
+impl Hook {
+    pub const ALL: [Hook; 6] = [
+        Hook::PreBuild,
+        Hook::PostBuild,
+        Hook::PreBump,
+        Hook::PostBump,
+        Hook::PreBackup,
+        Hook::PostBackup,
+    ];
+
+    pub fn parse(value: &str) -> Result<Self, ArkhamError> {
+        Hook::ALL
+            .into_iter()
+            .find(|hook| hook.name() == value.trim().to_lowercase())
+            .ok_or(ArkhamError::ConfigError(format!(
+                "Unknown hook '{}' (expected: pre_build | post_build | pre_bump | post_bump | pre_backup | post_backup)",
+                value.trim()
+            )))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hook::PreBuild => "pre_build",
+            Hook::PostBuild => "post_build",
+            Hook::PreBump => "pre_bump",
+            Hook::PostBump => "post_bump",
+            Hook::PreBackup => "pre_backup",
+            Hook::PostBackup => "post_backup",
+        }
+    }
+
+    // pre_* hooks can stop the operation, post_* ones only report
+    pub fn is_pre(&self) -> bool {
+        matches!(self, Hook::PreBuild | Hook::PreBump | Hook::PreBackup)
+    }
+}
+
+// 65536, 64K, 64KiB, 1.5M, 2G : bytes (K = 1024)
+pub fn parse_size(value: &str) -> Result<u64, ArkhamError> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let multiplier: u64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => 0,
+    };
+    match number.parse::<f64>() {
+        Ok(number) if multiplier > 0 && number >= 0.0 => Ok((number * multiplier as f64) as u64),
+        _ => Err(ArkhamError::ConfigError(format!(
+            "Invalid size '{}' (expected bytes, or a number with K | M | G)",
+            value
+        ))),
+    }
+}
+
+// How much a build may grow over the previous one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeGrowth {
+    Bytes(u64),
+    Percent(f64),
+}
+
+impl SizeGrowth {
+    // 5% or a size (4K)
+    pub fn parse(value: &str) -> Result<Self, ArkhamError> {
+        match value.trim().strip_suffix('%') {
+            Some(percent) => match percent.trim().parse::<f64>() {
+                Ok(percent) if percent >= 0.0 => Ok(SizeGrowth::Percent(percent)),
+                _ => Err(ArkhamError::ConfigError(format!(
+                    "Invalid size growth '{}' (expected a percentage like 5%, or a size like 4K)",
+                    value.trim()
+                ))),
+            },
+            None => parse_size(value).map(SizeGrowth::Bytes),
+        }
+    }
+
+    // Allowed growth in bytes over previous_bytes
+    pub fn limit(&self, previous_bytes: u64) -> u64 {
+        match self {
+            SizeGrowth::Bytes(bytes) => *bytes,
+            SizeGrowth::Percent(percent) => (previous_bytes as f64 * percent / 100.0) as u64,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            SizeGrowth::Bytes(bytes) => bytes.to_string(),
+            SizeGrowth::Percent(percent) => format!("{}%", percent),
+        }
+    }
+}
+
+// What a size limit being exceeded does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeGateAction {
+    Warn, // report, bump anyway
+    Fail, // no version bump
+}
+
+impl SizeGateAction {
+    pub fn parse(value: &str) -> Result<Self, ArkhamError> {
+        match value.trim().to_lowercase().as_str() {
+            "warn" => Ok(SizeGateAction::Warn),
+            "fail" => Ok(SizeGateAction::Fail),
+            other => Err(ArkhamError::ConfigError(format!(
+                "Unknown size gate '{}' (expected: warn | fail)",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SizeGateAction::Warn => "warn",
+            SizeGateAction::Fail => "fail",
+        }
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ArkhamConfig {
+    pub backup_backend: BackupBackend,
+    pub build_system: Option<BuildSystem>, // None => detected from the project files
+    pub hooks: Vec<(Hook, String)>,        // in file order, a stage may have several
+    pub size_budget: Option<u64>,          // max bytes for each stamped artifact
+    pub size_growth_limit: Option<SizeGrowth>, // max growth over the previous build
+    pub size_gate: SizeGateAction,
+    pub signing_key: Option<String>, // ed25519 private key (PEM) signing every stamped build
+    pub verify_key: Option<String>,  // public key arkham verify checks against by default
+}
+
+impl Default for ArkhamConfig {
+    fn default() -> Self {
+        ArkhamConfig {
+            backup_backend: BackupBackend::Git,
+            build_system: None,
+            hooks: Vec::new(),
+            size_budget: None,
+            size_growth_limit: None,
+            size_gate: SizeGateAction::Fail,
+            signing_key: None,
+            verify_key: None,
+        }
+    }
+}
+
+impl ArkhamConfig {
+    // Commands for one stage, in file order
+    pub fn hooks_for(&self, hook: Hook) -> Vec<&str> {
+        self.hooks
+            .iter()
+            .filter(|(stage, _)| *stage == hook)
+            .map(|(_, command)| command.as_str())
+            .collect()
+    }
+}
+
+// signing_key= / verify_key= : a path, relative to the project root unless absolute
+fn key_path(value: &str, line_num: usize) -> Result<String, ArkhamError> {
+    match value.trim() {
+        "" => Err(ArkhamError::ConfigError(format!(
+            "{} line {}: key file path is empty",
+            CONFIG_FILE,
+            line_num + 1
+        ))),
+        path => Ok(path.to_string()),
+    }
+}
+
+// Reading Config From File : .arkham.conf (missing file => defaults)
+pub fn read_config(project_root: &Path) -> Result<ArkhamConfig, ArkhamError> {
+    let mut config = ArkhamConfig::default();
+
+    let config_path = project_root.join(CONFIG_FILE);
+    if !config_path.exists() {
+        return Ok(config);
+    }
+
+    trace_read(&config_path);
+    let reader = BufReader::new(File::open(&config_path)?);
+    for (line_num, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        // Skip blanks & comments
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parse_info: Vec<&str> = line.splitn(2, '=').collect(); // Eg; backup_backend=tarball
+        if parse_info.len() != 2 {
+            return Err(ArkhamError::ConfigError(format!(
+                "{} line {}: expected key=value",
+                CONFIG_FILE,
+                line_num + 1
+            )));
+        }
+
+        match parse_info[0].trim() {
+            "backup_backend" => config.backup_backend = BackupBackend::parse(parse_info[1])?,
+            "build_system" => config.build_system = Some(BuildSystem::parse(parse_info[1])?),
+            "size_budget" => config.size_budget = Some(parse_size(parse_info[1])?),
+            "size_growth_limit" => {
+                config.size_growth_limit = Some(SizeGrowth::parse(parse_info[1])?)
+            }
+            "size_gate" => config.size_gate = SizeGateAction::parse(parse_info[1])?,
+            "signing_key" => config.signing_key = Some(key_path(parse_info[1], line_num)?),
+            "verify_key" => config.verify_key = Some(key_path(parse_info[1], line_num)?),
+            key if key.starts_with("hook.") => {
+                let hook = Hook::parse(&key["hook.".len()..])?;
+                let command = parse_info[1].trim();
+                if command.is_empty() {
+                    return Err(ArkhamError::ConfigError(format!(
+                        "{} line {}: {} has no command",
+                        CONFIG_FILE,
+                        line_num + 1,
+                        key
+                    )));
+                }
+                config.hooks.push((hook, command.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(config)
+}
+
+// Configured build system, else detected, else make
+pub fn resolve_build_system(project_root: &Path) -> Result<BuildSystem, ArkhamError> {
+    Ok(read_config(project_root)?
+        .build_system
+        .or(BuildSystem::detect(project_root))
+        .unwrap_or(BuildSystem::Make))
+}
+
+// Write Config to File : .arkham.conf
+pub fn write_config(project_root: &Path, config: &ArkhamConfig) -> Result<(), ArkhamError> {
+    let mut contents = String::from("# Arkham project configuration\n");
+    contents.push_str("# build_system   : make | cmake | cargo\n");
+    contents.push_str("# backup_backend : git | tarball\n");
+    contents.push_str("# hook.<stage>   : shell command, stage = pre|post _build|_bump|_backup\n");
+    contents.push_str("# size_budget    : max size of each artifact (64K, 1.5M ..)\n");
+    contents.push_str(
+        "# size_growth_limit : max growth of each artifact over its previous build (5% or 4K)\n",
+    );
+    contents.push_str("# size_gate      : fail | warn, what going over either limit does\n");
+    contents.push_str(
+        "# signing_key    : ed25519 private key (PKCS#8 PEM) signing every stamped build\n",
+    );
+    contents.push_str("# verify_key     : ed25519 public key (PEM) for arkham verify\n");
+    if let Some(build_system) = config.build_system {
+        contents.push_str(&format!("build_system={}\n", build_system.name()));
+    }
+    contents.push_str(&format!(
+        "backup_backend={}\n",
+        config.backup_backend.name()
+    ));
+    for (hook, command) in &config.hooks {
+        contents.push_str(&format!("hook.{}={}\n", hook.name(), command));
+    }
+    if let Some(budget) = config.size_budget {
+        contents.push_str(&format!("size_budget={}\n", budget));
+    }
+    if let Some(growth) = config.size_growth_limit {
+        contents.push_str(&format!("size_growth_limit={}\n", growth.name()));
+    }
+    if config.size_budget.is_some() || config.size_growth_limit.is_some() {
+        contents.push_str(&format!("size_gate={}\n", config.size_gate.name()));
+    }
+    if let Some(signing_key) = &config.signing_key {
+        contents.push_str(&format!("signing_key={}\n", signing_key));
+    }
+    if let Some(verify_key) = &config.verify_key {
+        contents.push_str(&format!("verify_key={}\n", verify_key));
+    }
+    write_atomic(&project_root.join(CONFIG_FILE), contents.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_with_and_without_units() {
+        assert_eq!(parse_size("65536").unwrap(), 65536);
+        assert_eq!(parse_size("4K").unwrap(), 4096);
+        assert_eq!(parse_size(" 64KiB ").unwrap(), 65536);
+        assert_eq!(parse_size("1.5M").unwrap(), 1536 * 1024);
+        assert_eq!(parse_size("2gb").unwrap(), 2 << 30);
+        assert_eq!(parse_size("10 b").unwrap(), 10);
+    }
+
+    #[test]
+    fn bad_sizes_are_config_errors() {
+        for value in ["", "K", "4X", "-4K", "4KK", "1.2.3M"] {
+            assert!(
+                matches!(parse_size(value), Err(ArkhamError::ConfigError(_))),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn growth_as_percent_or_size() {
+        assert_eq!(SizeGrowth::parse("5%").unwrap(), SizeGrowth::Percent(5.0));
+        assert_eq!(SizeGrowth::parse("4K").unwrap(), SizeGrowth::Bytes(4096));
+        assert_eq!(SizeGrowth::parse("5%").unwrap().limit(200_000), 10_000);
+        assert_eq!(SizeGrowth::parse("4K").unwrap().limit(200_000), 4096);
+        assert!(SizeGrowth::parse("-5%").is_err());
+        assert!(SizeGrowth::parse("five%").is_err());
+    }
+}
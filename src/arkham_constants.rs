@@ -2,35 +2,93 @@ extern crate tabled;
 use tabled::Tabled;
 
 // ARKHAM system files
-pub(crate) const ARKHAM_VER: &str = "1.4";
-pub(crate) const VERSION_INFO_FILE: &str = ".version.info";
-pub(crate) const VERSION_LOGS_FILE: &str = ".version.log";
-pub(crate) const LATEST_BUILD_LOGS: &str = "latest_build-log.LOG";
-pub(crate) const ARKHAM_ASCII_LOGO: &str = "Arkham";
-pub(crate) const AUTHOR: &str = "Aditya Navphule";
-pub(crate) const AUTHOR_KNOX_ID: &str = "aditya.sn2";
-pub(crate) const DISPLAY_HEADER_CHAR: char = '=';
-pub(crate) const DEBUG_MODE: bool = false; // enabled when run as ./arkham -DEBUG build // work in progress
+pub const ARKHAM_VER: &str = "1.4";
+pub const VERSION_INFO_FILE: &str = ".version.info";
+pub const VERSION_LOGS_FILE: &str = ".version.log";
+pub const VERSION_LOG_HEADER: &str =
+    "version_name,version_log,build_date,built_by,commit_id,build_ms,artifact_bytes,warnings,errors";
+// Columns in VERSION_LOG_HEADER : every .version.log row has exactly this many
+pub const LOG_FIELDS: usize = 9;
+// .version.stats : one row per built version, written when the bump is committed
+pub const BUILD_STATS_HEADER: &str =
+    "version_name,build_date,build_ms,artifact_bytes,warnings,errors";
+// State file layouts understood by this arkham (see arkham_schema)
+pub const INFO_SCHEMA_VERSION: u32 = 3;
+pub const LOG_SCHEMA_VERSION: u32 = 4;
+pub const LOG_SCHEMA_MARKER: &str = "#schema=";
+pub const MIGRATION_BACKUPS: &str = ".version*.schema*.bak"; // state files as they were before a migration
+pub const LATEST_BUILD_LOGS: &str = "latest_build-log.LOG";
+pub const CONFIG_FILE: &str = ".arkham.conf";
+pub const WORKSPACE_FILE: &str = ".arkham.workspace";
+pub const COMPONENT_STATE_FILES: &str = ".version.*"; // .version.<component>.info / .log
+pub const LOCK_FILE: &str = ".arkham.lock"; // advisory lock held while state is being changed
+pub const LOCK_HELD_ENV: &str = "ARKHAM_LOCK_HELD"; // set for hooks : the parent arkham holds this lock
+pub const TEMP_FILE_SUFFIX: &str = ".arkham-tmp"; // state file being written, renamed over the real one when done
+pub const JOURNAL_FILE: &str = ".arkham.journal"; // undo steps of a version update in flight : <file>.<pid>.arkham-tmp
+pub const JOURNAL_FILES: &str = ".arkham.journal.*";
+pub const REPAIR_BACKUPS: &str = ".version*.log.bak"; // arkham repair : the log as it was
+pub const REPAIR_QUARANTINE: &str = ".version*.log.quarantine"; // arkham repair : rows it couldn't fix
+pub const IGNORE_FILE: &str = ".arkhamignore";
+pub const PREV_BUILDS_DIR: &str = "prev_builds";
+pub const MAX_PREV_BUILDS: usize = 10;
+pub const CHECKSUM_EXTENSION: &str = ".sha256";
+pub const SIGNATURE_EXTENSION: &str = ".sig"; // detached ed25519 signature of one build
 
-// ARKHAM GIT PROTECTED FILES
-pub(crate) const ARKHAM_PROTECTED_FILES: [&str; 5] = [
+// Files kept next to a build in prev_builds/, never counted as builds themselves
+pub const BUILD_SIDECAR_EXTENSIONS: [&str; 3] =
+    [".version", CHECKSUM_EXTENSION, SIGNATURE_EXTENSION];
+pub const ARKHAM_ASCII_LOGO: &str = "Arkham";
+pub const AUTHOR: &str = "Aditya Navphule";
+pub const AUTHOR_KNOX_ID: &str = "aditya.sn2";
+pub const DISPLAY_HEADER_CHAR: char = '=';
+pub const DEBUG_ENV: &str = "ARKHAM_DEBUG"; // ARKHAM_DEBUG=1 arkham build == arkham --debug build
+pub const TRACE_FILE_ENV: &str = "ARKHAM_TRACE_FILE"; // trace appended here as well
+
+// ARKHAM SNAPSHOT BACKEND (git-free backups)
+pub const SNAPSHOT_DIR: &str = ".arkham_snapshots";
+pub const SNAPSHOT_ID_PREFIX: &str = "snap-";
+
+// ARKHAM GIT PROTECTED FILES : never captured in snapshots or touched by a restore
+pub const ARKHAM_PROTECTED_FILES: [&str; 17] = [
     "arkham",
     VERSION_INFO_FILE,
     VERSION_LOGS_FILE,
     LATEST_BUILD_LOGS,
     "build_logs",
+    CONFIG_FILE,
+    WORKSPACE_FILE,
+    COMPONENT_STATE_FILES,
+    LOCK_FILE,
+    "*.arkham-tmp",
+    JOURNAL_FILES,
+    MIGRATION_BACKUPS,
+    REPAIR_BACKUPS,
+    REPAIR_QUARANTINE,
+    SNAPSHOT_DIR,
+    PREV_BUILDS_DIR,
+    ".git",
+];
+
+// Left behind by arkham itself : protected above & never committed by a git backup
+pub const ARKHAM_SCRATCH_FILES: [&str; 6] = [
+    LOCK_FILE,
+    "*.arkham-tmp",
+    JOURNAL_FILES,
+    MIGRATION_BACKUPS,
+    REPAIR_BACKUPS,
+    REPAIR_QUARANTINE,
 ];
 
 #[derive(Tabled)]
-pub(crate) struct VersionRecord {
+pub struct VersionRecord {
     #[tabled(rename = "Version Name\n(oldest to newest)")]
-    pub(crate) version: String,
+    pub version: String,
     #[tabled(rename = "Version Log")]
-    pub(crate) log: String,
+    pub log: String,
     #[tabled(rename = "Build Date")]
-    pub(crate) date: String,
+    pub date: String,
     #[tabled(rename = "Built By")]
-    pub(crate) builder: String,
+    pub builder: String,
     #[tabled(rename = "Commit ID(Truncated)")]
-    pub(crate) commit: String,
+    pub commit: String,
 }
@@ -0,0 +1,561 @@
+/*
+ ARKHAM DOCTOR : consistency check over everything arkham keeps for a project
+*/
+use crate::arkham_constants::{
+    BUILD_SIDECAR_EXTENSIONS, CHECKSUM_EXTENSION, LOG_FIELDS, TEMP_FILE_SUFFIX, VERSION_LOG_HEADER,
+};
+use crate::arkham_errors::{validate_version, ArkhamError};
+use crate::arkham_project::ProjectContext;
+use crate::arkham_render::fit_to_terminal;
+use crate::arkham_schema::read_log_lines;
+use crate::arkham_snapshot::{is_snapshot_id, snapshot_path};
+use crate::arkham_trace::run_output;
+use crate::arkham_utility::{display_error_msg, display_header_msg, display_success_msg};
+use crate::arkham_version::{
+    artifact_file_name, parse_log_row, read_version_info, verify_version_info, versioned_path,
+    ProjectInfo,
+};
+
+use std::fs;
+use std::process::Command;
+use tabled::{
+    settings::{object::Segment, Alignment, Modify, Padding, Style},
+    Table, Tabled,
+};
+
+#[derive(Tabled)]
+struct Finding {
+    #[tabled(rename = "Check")]
+    check: String,
+    #[tabled(rename = "Status")]
+    status: &'static str,
+    #[tabled(rename = "Problem")]
+    problem: String,
+    #[tabled(rename = "Suggested Fix")]
+    fix: String,
+}
+
+// Collected findings : WARN = worth a look, FAIL = arkham state is broken
+#[derive(Default)]
+struct Report {
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    fn warn(&mut self, check: &str, problem: String, fix: String) {
+        self.add(check, "WARN", problem, fix);
+    }
+
+    fn fail(&mut self, check: &str, problem: String, fix: String) {
+        self.add(check, "FAIL", problem, fix);
+    }
+
+    fn add(&mut self, check: &str, status: &'static str, problem: String, fix: String) {
+        self.findings.push(Finding {
+            check: check.to_string(),
+            status,
+            problem,
+            fix,
+        });
+    }
+
+    fn failures(&self) -> usize {
+        self.findings.iter().filter(|f| f.status == "FAIL").count()
+    }
+}
+
+// git object exists & is a commit
+fn commit_exists(context: &ProjectContext, commit_id: &str) -> bool {
+    run_output(Command::new("git").current_dir(&context.root).args([
+        "cat-file",
+        "-e",
+        &format!("{}^{{commit}}", commit_id),
+    ]))
+    .map(|output| output.status.success())
+    .unwrap_or(false)
+}
+
+// .version.info
+fn check_version_info(context: &ProjectContext, report: &mut Report) -> Option<ProjectInfo> {
+    let info_file = context.info_file();
+    match read_version_info(context) {
+        Ok(Some(info)) => {
+            if let Err(e) = validate_version(&info.current_version) {
+                report.fail(
+                    "version info",
+                    format!("{}: {}", info_file.display(), e),
+                    "Fix current_version by hand (X.YY)".to_string(),
+                );
+            }
+            Some(info)
+        }
+        Ok(None) => {
+            report.fail(
+                "version info",
+                format!("{} is missing", info_file.display()),
+                "Run 'arkham init' (or restore the file from a backup)".to_string(),
+            );
+            None
+        }
+        Err(e) => {
+            report.fail(
+                "version info",
+                format!("{} does not parse: {}", info_file.display(), e),
+                "Restore it from a backup or re-create it with 'arkham init --force'".to_string(),
+            );
+            None
+        }
+    }
+}
+
+// .version.log rows : returns the last valid logged version
+fn check_version_log(context: &ProjectContext, report: &mut Report) -> Option<String> {
+    let log_path = context.log_file();
+    if !log_path.exists() {
+        return None;
+    }
+
+    let log = match read_log_lines(&log_path) {
+        Ok(log) => log,
+        Err(e) => {
+            report.fail(
+                "version log",
+                format!("{} can't be read: {}", log_path.display(), e),
+                "Check the file permissions / schema marker, or run 'arkham repair'".to_string(),
+            );
+            return None;
+        }
+    };
+
+    if let Some((_, ref header)) = log.header {
+        if header != VERSION_LOG_HEADER {
+            report.warn(
+                "version log",
+                format!("unexpected header '{}'", header),
+                "Run 'arkham repair' to restore the canonical header".to_string(),
+            );
+        }
+    }
+
+    let mut last_logged = None;
+    let git_available = context.root.join(".git").exists();
+
+    for (line_num, line) in log.rows {
+        let fields = parse_log_row(&line);
+        if fields.len() != LOG_FIELDS {
+            report.fail(
+                "version log",
+                format!(
+                    "line {}: expected {} fields, found {}",
+                    line_num,
+                    LOG_FIELDS,
+                    fields.len()
+                ),
+                "Run 'arkham repair' to re-quote or quarantine the row".to_string(),
+            );
+            continue;
+        }
+        if let Err(e) = validate_version(&fields[0]) {
+            report.fail(
+                "version log",
+                format!("line {}: {}", line_num, e),
+                "Run 'arkham repair' to quarantine the row".to_string(),
+            );
+            continue;
+        }
+        last_logged = Some(fields[0].clone());
+
+        // Backup IDs : snapshot archives or git commits
+        let state_id = &fields[4];
+        if state_id.is_empty() {
+            report.warn(
+                "version log",
+                format!("line {}: version {} has no commit ID", line_num, fields[0]),
+                "Run 'arkham backup' to save the state again".to_string(),
+            );
+        } else if is_snapshot_id(state_id) {
+            if !snapshot_path(&context.root, state_id).exists() {
+                report.fail(
+                    "version log",
+                    format!(
+                        "line {}: snapshot {} of version {} is missing",
+                        line_num, state_id, fields[0]
+                    ),
+                    "Restore the archive into the snapshot directory, or re-run 'arkham backup'"
+                        .to_string(),
+                );
+            }
+        } else {
+            if !git_available {
+                report.fail(
+                    "version log",
+                    format!(
+                        "line {}: commit {} logged but {} is not a git repo",
+                        line_num,
+                        state_id,
+                        context.root.display()
+                    ),
+                    "Restore the .git directory, or back up again with '--backend tarball'"
+                        .to_string(),
+                );
+            } else if !commit_exists(context, state_id) {
+                report.fail(
+                    "version log",
+                    format!(
+                        "line {}: commit {} of version {} not found in git",
+                        line_num, state_id, fields[0]
+                    ),
+                    "Fetch the missing history, or run 'arkham backup' to log a fresh commit"
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    last_logged
+}
+
+// Stamped copies, symlinks & what's sitting in prev_builds/
+fn check_builds(context: &ProjectContext, info: &ProjectInfo, report: &mut Report) {
+    for artifact in &info.artifacts {
+        let link = context.path(artifact);
+        let expected = versioned_path(context, artifact, &info.current_version);
+        let expected_name = expected.file_name().unwrap_or_default();
+
+        match fs::read_link(&link) {
+            Ok(target) => {
+                if target.file_name() != Some(expected_name) {
+                    report.warn(
+                        "symlink",
+                        format!(
+                            "{} -> {}, expected {}",
+                            artifact,
+                            target.display(),
+                            expected_name.to_string_lossy()
+                        ),
+                        format!(
+                            "ln -sfn {} {}",
+                            expected_name.to_string_lossy(),
+                            link.display()
+                        ),
+                    );
+                }
+                if !link.exists() {
+                    report.fail(
+                        "symlink",
+                        format!("{} -> {} is broken", artifact, target.display()),
+                        "Run 'arkham build' to rebuild & re-stamp the current version".to_string(),
+                    );
+                    continue;
+                }
+            }
+            Err(_) if link.exists() => {
+                report.warn(
+                    "symlink",
+                    format!(
+                        "{} is a plain file, not a symlink to a stamped build",
+                        artifact
+                    ),
+                    "Run 'arkham build' to stamp & link it".to_string(),
+                );
+                continue;
+            }
+            Err(_) => {
+                report.fail(
+                    "symlink",
+                    format!("{} does not exist", artifact),
+                    "Run 'arkham build' to rebuild & re-stamp the current version".to_string(),
+                );
+                continue;
+            }
+        }
+
+        match verify_version_info(&link, &info.current_version) {
+            Ok(true) => {}
+            Ok(false) => report.fail(
+                "stamp",
+                format!(
+                    "{} does not carry 'Version: {}'",
+                    artifact, info.current_version
+                ),
+                "Run 'arkham build' and keep the version to re-stamp it".to_string(),
+            ),
+            Err(e) => report.warn(
+                "stamp",
+                format!("could not read the stamp of {}: {}", artifact, e),
+                "Check the file permissions and run 'arkham doctor' again".to_string(),
+            ),
+        }
+    }
+
+    check_prev_builds(context, info, report);
+}
+
+// Every prev_builds entry belongs to a known artifact, and the live version isn't duplicated there
+fn check_prev_builds(context: &ProjectContext, info: &ProjectInfo, report: &mut Report) {
+    let prev_builds_dir = context.prev_builds_dir();
+    let entries = match fs::read_dir(&prev_builds_dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    let mut prefixes: Vec<String> = info
+        .artifacts
+        .iter()
+        .map(|artifact| format!("{}_v_", artifact_file_name(artifact)))
+        .collect();
+    prefixes.push(format!("{}_v_", info.project_name));
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+
+        // Workspace : prev_builds/<component>/ directories belong to other components
+        if context.component.is_none() && path.is_dir() {
+            continue;
+        }
+
+        if file_name.ends_with(TEMP_FILE_SUFFIX) {
+            report.warn(
+                "prev_builds",
+                format!("{} is left over from an interrupted run", file_name),
+                format!("rm {}", path.display()),
+            );
+            continue;
+        }
+
+        // <artifact>_v_<version>[.sha256|.version]
+        let mut version_part = None;
+        for prefix in &prefixes {
+            if let Some(rest) = file_name.strip_prefix(prefix.as_str()) {
+                let rest = BUILD_SIDECAR_EXTENSIONS
+                    .iter()
+                    .find_map(|ext| rest.strip_suffix(ext))
+                    .unwrap_or(rest);
+                if validate_version(rest).is_ok() {
+                    version_part = Some(rest.to_string());
+                    break;
+                }
+            }
+        }
+
+        match version_part {
+            None => report.warn(
+                "prev_builds",
+                format!(
+                    "orphan {}: not a build of {}",
+                    file_name,
+                    info.artifacts.join(", ")
+                ),
+                format!("Move it out of {} or delete it", prev_builds_dir.display()),
+            ),
+            Some(version) if version == info.current_version => {
+                let live = if file_name.ends_with(CHECKSUM_EXTENSION) {
+                    context.path(&file_name)
+                } else {
+                    prefixes
+                        .iter()
+                        .zip(&info.artifacts)
+                        .find(|(prefix, _)| file_name.starts_with(prefix.as_str()))
+                        .map(|(_, artifact)| versioned_path(context, artifact, &version))
+                        .unwrap_or(context.path(&file_name))
+                };
+                if live.exists() {
+                    report.warn(
+                        "prev_builds",
+                        format!(
+                            "duplicate {}: the current version also lives at {}",
+                            file_name,
+                            live.display()
+                        ),
+                        format!("rm {}", path.display()),
+                    );
+                }
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+// arkham doctor
+pub fn run_doctor(context: &ProjectContext) -> Result<(), ArkhamError> {
+    let mut report = Report::default();
+
+    let info = check_version_info(context, &mut report);
+    let last_logged = check_version_log(context, &mut report);
+
+    if let Some(ref info) = info {
+        match last_logged {
+            Some(ref logged) if logged != &info.current_version => report.warn(
+                "current version",
+                format!(
+                    "current_version is {} but the latest logged version is {}",
+                    info.current_version, logged
+                ),
+                format!(
+                    "Run 'arkham backup' to log {} (or fix current_version)",
+                    info.current_version
+                ),
+            ),
+            None => report.warn(
+                "current version",
+                format!("version {} was never logged", info.current_version),
+                "Run 'arkham backup' to save & log it".to_string(),
+            ),
+            _ => {}
+        }
+
+        check_builds(context, info, &mut report);
+    }
+
+    if report.findings.is_empty() {
+        display_success_msg("arkham doctor: everything looks fine!");
+        return Ok(());
+    }
+
+    let failures = report.failures();
+    let header = match failures {
+        0 => display_header_msg,
+        _ => display_error_msg,
+    };
+    header(&format!(
+        "arkham doctor: {} problem(s), {} warning(s)",
+        failures,
+        report.findings.len() - failures
+    ));
+
+    let mut table = Table::new(report.findings);
+    let styled_table = table
+        .with(Style::ascii())
+        .with(Padding::new(1, 1, 0, 0))
+        .with(Modify::new(Segment::all()).with(Alignment::center()))
+        .with(fit_to_terminal());
+    println!("{}\n", styled_table);
+
+    if failures > 0 {
+        return Err(ArkhamError::CorruptVersionInfo(format!(
+            "arkham doctor found {} problem(s)",
+            failures
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arkham_constants::SNAPSHOT_DIR;
+    use crate::arkham_schema::log_preamble;
+    use crate::arkham_version::{format_log_row, update_executable_version, write_version_info};
+
+    // main at 1.01, stamped & linked, 1.00 rotated into prev_builds/ & logged with a snapshot
+    fn healthy_project() -> (tempfile::TempDir, ProjectContext, ProjectInfo) {
+        let dir = tempfile::tempdir().unwrap();
+        let context = ProjectContext::at(dir.path());
+        let mut info = ProjectInfo::new(
+            "main".to_string(),
+            "1.00".to_string(),
+            dir.path().to_string_lossy().to_string(),
+        );
+        write_version_info(&context, &info).unwrap();
+        for version in ["1.00", "1.01"] {
+            let _ = fs::remove_file(context.path("main"));
+            fs::write(context.path("main"), "build").unwrap();
+            update_executable_version(&context, &info, version).unwrap();
+            info = read_version_info(&context).unwrap().unwrap();
+        }
+
+        fs::create_dir_all(context.path(SNAPSHOT_DIR)).unwrap();
+        fs::write(snapshot_path(&context.root, "snap-1.01-1"), "").unwrap();
+        let row = format_log_row(&[
+            "1.01",
+            "second",
+            "2026-01-01 10:00:00",
+            "me",
+            "snap-1.01-1",
+            "",
+            "",
+            "",
+            "",
+        ]);
+        fs::write(context.log_file(), format!("{}{}\n", log_preamble(), row)).unwrap();
+        (dir, context, info)
+    }
+
+    fn problems(report: &Report) -> Vec<(&str, &str)> {
+        report
+            .findings
+            .iter()
+            .map(|f| (f.status, f.problem.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn healthy_project_has_no_findings() {
+        let (_dir, context, info) = healthy_project();
+        let mut report = Report::default();
+        assert_eq!(
+            check_version_log(&context, &mut report),
+            Some("1.01".to_string())
+        );
+        check_builds(&context, &info, &mut report);
+        assert!(report.findings.is_empty(), "{:?}", problems(&report));
+        assert!(run_doctor(&context).is_ok());
+    }
+
+    #[test]
+    fn broken_log_rows_fail() {
+        let (_dir, context, _info) = healthy_project();
+        let rows = [
+            format_log_row(&["1.02", "gone", "d", "me", "snap-1.02-1", "", "", "", ""]),
+            "1.03,too,few".to_string(),
+            format_log_row(&["v1", "bad", "d", "me", "snap-1.01-1", "", "", "", ""]),
+        ];
+        let mut log = fs::read_to_string(context.log_file()).unwrap();
+        log.push_str(&format!("{}\n", rows.join("\n")));
+        fs::write(context.log_file(), log).unwrap();
+
+        let mut report = Report::default();
+        assert_eq!(
+            check_version_log(&context, &mut report),
+            Some("1.02".to_string())
+        );
+        let found = problems(&report);
+        assert_eq!(report.failures(), 3, "{:?}", found);
+        assert!(found[0]
+            .1
+            .contains("snapshot snap-1.02-1 of version 1.02 is missing"));
+        assert!(found[1].1.contains("expected 9 fields, found 3"));
+        assert!(run_doctor(&context).is_err());
+    }
+
+    #[test]
+    fn stray_builds_are_flagged() {
+        let (_dir, context, info) = healthy_project();
+        let prev_builds = context.prev_builds_dir();
+        fs::write(prev_builds.join("notes.txt"), "").unwrap();
+        fs::write(prev_builds.join("main_v_1.01"), "duplicate").unwrap();
+        fs::write(prev_builds.join("main_v_1.00.new.1.arkham-tmp"), "").unwrap();
+        // a plain file where the symlink should be
+        fs::remove_file(context.path("main")).unwrap();
+        fs::write(context.path("main"), "unstamped").unwrap();
+
+        let mut report = Report::default();
+        check_builds(&context, &info, &mut report);
+        let found = problems(&report);
+        assert_eq!(report.failures(), 0, "{:?}", found);
+        for expected in [
+            "main is a plain file",
+            "orphan notes.txt",
+            "duplicate main_v_1.01",
+            "main_v_1.00.new.1.arkham-tmp is left over",
+        ] {
+            assert!(
+                found.iter().any(|(_, problem)| problem.contains(expected)),
+                "{} not in {:?}",
+                expected,
+                found
+            );
+        }
+    }
+}
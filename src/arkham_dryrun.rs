@@ -0,0 +1,83 @@
+/*
+ DRY RUN : --dry-run on build, backup & clean
+    the real code paths record what they would do here instead of doing it
+*/
+use crate::arkham_project::ProjectContext;
+use crate::arkham_trace::command_line;
+use crate::arkham_utility::display_header_msg;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub struct DryRun {
+    root: PathBuf,
+    steps: Vec<(&'static str, String)>,
+}
+
+impl DryRun {
+    pub fn new(context: &ProjectContext) -> Self {
+        DryRun {
+            root: context.root.clone(),
+            steps: Vec::new(),
+        }
+    }
+
+    // Paths inside the project relative to its root, the rest as they are
+    fn show(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    pub fn run(&mut self, cmd: &Command) {
+        let cwd = cmd
+            .get_current_dir()
+            .map(|dir| format!("  (in {})", dir.display()))
+            .unwrap_or_default();
+        self.steps
+            .push(("run", format!("{}{}", command_line(cmd), cwd)));
+    }
+
+    pub fn create(&mut self, path: &Path, what: &str) {
+        let step = format!("{}  ({})", self.show(path), what);
+        self.steps.push(("create", step));
+    }
+
+    // State file change : what goes in, or how it changes
+    pub fn write(&mut self, path: &Path, change: &str) {
+        let step = format!("{}  {}", self.show(path), change);
+        self.steps.push(("write", step));
+    }
+
+    pub fn rename(&mut self, from: &Path, to: &Path) {
+        let step = format!("{} -> {}", self.show(from), self.show(to));
+        self.steps.push(("rename", step));
+    }
+
+    pub fn symlink(&mut self, link: &Path, target: &Path) {
+        let step = format!("{} -> {}", self.show(link), target.display());
+        self.steps.push(("symlink", step));
+    }
+
+    pub fn delete(&mut self, path: &Path, why: &str) {
+        let step = format!("{}  ({})", self.show(path), why);
+        self.steps.push(("delete", step));
+    }
+
+    pub fn note(&mut self, note: &str) {
+        self.steps.push(("note", note.to_string()));
+    }
+
+    pub fn print(&self, title: &str) {
+        display_header_msg(&format!("Dry run: {}\nnothing has been changed", title));
+        if self.steps.is_empty() {
+            println!("Nothing to do.");
+            return;
+        }
+        for (action, step) in &self.steps {
+            println!("  {:<8} {}", action, step);
+        }
+        println!();
+    }
+}
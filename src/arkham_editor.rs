@@ -0,0 +1,166 @@
+/*
+ LOG MESSAGE EDITOR : arkham backup opens $VISUAL / $EDITOR on a template, the way git commit does
+    '#' lines are dropped, an empty message aborts the backup
+    no editor set, stdin not a terminal or --no-edit => the old one line prompt
+*/
+use crate::arkham_constants::LOG_FIELDS;
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_schema::read_log_lines;
+use crate::arkham_snapshot::is_snapshot_id;
+use crate::arkham_trace::{run_output, run_status, trace_read};
+use crate::arkham_utility::get_user_input;
+use crate::arkham_version::{parse_log_row, ProjectInfo};
+
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+
+// $VISUAL first, then $EDITOR, like git
+fn configured_editor() -> Option<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|editor| editor.trim().to_string())
+        .find(|editor| !editor.is_empty())
+}
+
+// version & state ID of the newest log row
+fn last_logged(context: &ProjectContext) -> Option<(String, String)> {
+    let log_path = context.log_file();
+    if !log_path.exists() {
+        return None;
+    }
+
+    read_log_lines(&log_path)
+        .ok()?
+        .rows
+        .iter()
+        .rev()
+        .map(|(_, line)| parse_log_row(line))
+        .find(|fields| fields.len() == LOG_FIELDS)
+        .map(|fields| (fields[0].clone(), fields[4].clone()))
+}
+
+// git output as template comment lines, nothing if git has nothing to say (or isn't there)
+fn git_lines(project_root: &Path, args: &[&str]) -> Vec<String> {
+    if !project_root.join(".git").exists() {
+        return Vec::new();
+    }
+    match run_output(Command::new("git").current_dir(project_root).args(args)) {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| format!("#   {}", line))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn template(context: &ProjectContext, info: &ProjectInfo) -> String {
+    let project_root = context.root.as_path();
+    let mut lines = vec![
+        String::new(),
+        format!(
+            "# Version log for {} v{}",
+            info.project_name, info.current_version
+        ),
+        "# Write what changed above. Lines starting with '#' are ignored,".to_string(),
+        "# an empty message aborts the backup.".to_string(),
+    ];
+
+    // Commits since the last logged version (a snapshot ID means there's no commit to start from)
+    let (title, commits) = match last_logged(context) {
+        Some((version, state_id)) if !is_snapshot_id(&state_id) => (
+            format!("# Commits since v{}:", version),
+            git_lines(
+                project_root,
+                &["log", "--oneline", &format!("{}..HEAD", state_id)],
+            ),
+        ),
+        _ => (
+            "# Recent commits:".to_string(),
+            git_lines(project_root, &["log", "--oneline", "-20"]),
+        ),
+    };
+    if !commits.is_empty() {
+        lines.extend(["#".to_string(), title]);
+        lines.extend(commits);
+    }
+
+    let changes = git_lines(project_root, &["status", "--short"]);
+    if !changes.is_empty() {
+        lines.extend(["#".to_string(), "# Changes being saved:".to_string()]);
+        lines.extend(changes);
+    }
+
+    lines.join("\n") + "\n"
+}
+
+// Comments out, trailing spaces & blank lines around the message gone
+fn clean_message(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+// sh -c so "code --wait" or "vim -c 'set tw=72'" work as they do for git
+fn edit_file(editor: &str, path: &Path) -> Result<(), ArkhamError> {
+    let status = run_status(
+        Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$@\"", editor))
+            .arg(editor)
+            .arg(path),
+    )
+    .map_err(|e| ArkhamError::BackupError(format!("Failed to start editor '{}': {}", editor, e)))?;
+
+    if !status.success() {
+        return Err(ArkhamError::BackupError(format!(
+            "Editor '{}' exited with {}, backup aborted",
+            editor,
+            status
+                .code()
+                .map(|code| format!("exit code {}", code))
+                .unwrap_or("a signal".to_string())
+        )));
+    }
+    Ok(())
+}
+
+// The version log message for a backup, possibly several lines long
+pub fn compose_log_message(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    edit: bool,
+) -> Result<String, ArkhamError> {
+    let editor = match configured_editor() {
+        Some(editor) if edit && std::io::stdin().is_terminal() => editor,
+        _ => return get_user_input("Enter a message describing the changes : "),
+    };
+
+    // a fresh file of our own (O_EXCL, random name) : nothing planted in a shared /tmp gets written through,
+    // removed when dropped
+    let mut message_file = tempfile::Builder::new()
+        .prefix("arkham-VERSION_LOG-")
+        .suffix(".txt")
+        .tempfile()?;
+    message_file.write_all(template(context, info).as_bytes())?;
+    message_file.flush()?;
+
+    let path = message_file.path();
+    edit_file(&editor, path)?;
+    trace_read(path);
+    let message = clean_message(&fs::read_to_string(path)?);
+    if message.is_empty() {
+        return Err(ArkhamError::BackupError(
+            "Empty version log message, backup aborted".to_string(),
+        ));
+    }
+    Ok(message)
+}
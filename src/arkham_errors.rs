@@ -3,7 +3,8 @@ use std::{fmt, io};
 use std::error::Error;
 
 #[derive(Debug)] // For Easy Printing
-pub(crate) enum ArkhamError {
+#[non_exhaustive] // a new feature brings its own variant
+pub enum ArkhamError {
     IoError(io::Error),
     InvalidVersion(String),
     NoVersionSpecified,
@@ -13,6 +14,16 @@ pub(crate) enum ArkhamError {
     MissingVersionInfo,
     BuildError(String),
     BackupError(String),
+    ConfigError(String),
+    InitError(String),
+    WorkspaceError(String),
+    LockError(String),
+    SchemaError(String),
+    HookError(String),
+    SizeGateError(String),
+    WatchError(String),
+    TuiError(String),
+    SigningError(String),
 }
 
 // Custom error print format
@@ -43,6 +54,16 @@ impl fmt::Display for ArkhamError {
             Self::MissingVersionInfo => write!(f, "Version Information missing"),
             Self::BuildError(msg) => write!(f, "Build error: {}", msg),
             Self::BackupError(msg) => write!(f, "Error occurred during saving/restoring state!: {}", msg),
+            Self::ConfigError(msg) => write!(f, "Invalid configuration: {}", msg),
+            Self::InitError(msg) => write!(f, "Project setup failed: {}", msg),
+            Self::WorkspaceError(msg) => write!(f, "Workspace error: {}", msg),
+            Self::LockError(msg) => write!(f, "Project is locked: {}", msg),
+            Self::SchemaError(msg) => write!(f, "Unsupported state file: {}", msg),
+            Self::HookError(msg) => write!(f, "Hook failed: {}", msg),
+            Self::SizeGateError(msg) => write!(f, "Size gate: {}", msg),
+            Self::WatchError(msg) => write!(f, "Watch failed: {}", msg),
+            Self::TuiError(msg) => write!(f, "Terminal UI: {}", msg),
+            Self::SigningError(msg) => write!(f, "Signature: {}", msg),
         }
     }
 }
@@ -54,7 +75,7 @@ impl From<io::Error> for ArkhamError {
     }
 }
 
-pub(crate) fn validate_version(version: &str) -> Result<(), ArkhamError> {
+pub fn validate_version(version: &str) -> Result<(), ArkhamError> {
     // is of the format 4 (no decimal)
     if !version.contains('.') {
         return Err(ArkhamError::InvalidVersion(version.into()));
@@ -0,0 +1,149 @@
+/*
+ BUILD FINGERPRINT : is there anything to rebuild?
+    sha256 over the project files (the ones a snapshot would archive : ignore lists, no state files,
+    no artifacts) + build system, component target & build args
+    taken after every stamped build, kept in .version.info as last_build_fingerprint
+*/
+use crate::arkham_config::BuildSystem;
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_snapshot::snapshot_files;
+use crate::arkham_trace::trace_event;
+use crate::arkham_version::{artifact_file_name, ProjectInfo};
+use crate::arkham_workspace::tree_artifacts;
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+// Extra artifacts & their stamped copies are build outputs too (the executable is already ignored)
+pub fn is_artifact(info: &ProjectInfo, rel_path: &Path) -> bool {
+    let file_name = rel_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    info.artifacts.iter().any(|artifact| {
+        let artifact = Path::new(artifact);
+        rel_path == artifact
+            || (rel_path.parent() == artifact.parent()
+                && file_name.starts_with(&format!(
+                    "{}_v_",
+                    artifact_file_name(&artifact.to_string_lossy())
+                )))
+    })
+}
+
+pub fn build_fingerprint(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    build_system: BuildSystem,
+    args: &[String],
+) -> Result<String, ArkhamError> {
+    let started = Instant::now();
+    let mut hasher = Sha256::new();
+
+    // How it gets built
+    hasher.update(build_system.name());
+    hasher.update([0]);
+    hasher.update(context.build_target().unwrap_or_default());
+    hasher.update([0]);
+    for arg in args {
+        hasher.update(arg);
+        hasher.update([0]);
+    }
+
+    // What it gets built from : path & contents (links by their target, never followed)
+    let files: Vec<_> = snapshot_files(&context.root, &tree_artifacts(context, info)?)?
+        .into_iter()
+        .filter(|rel_path| !is_artifact(info, rel_path))
+        .collect();
+    for rel_path in &files {
+        let path = context.root.join(rel_path);
+        hasher.update(rel_path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        if path.is_symlink() {
+            hasher.update(fs::read_link(&path)?.to_string_lossy().as_bytes());
+        } else {
+            hasher.update(Sha256::digest(fs::read(&path)?));
+        }
+        hasher.update([0]);
+    }
+
+    let fingerprint = format!("{:x}", hasher.finalize());
+    trace_event(
+        "fingerprint",
+        &[
+            ("files", files.len().to_string()),
+            ("sha256", fingerprint.clone()),
+            ("took_ms", started.elapsed().as_millis().to_string()),
+        ],
+    );
+    Ok(fingerprint)
+}
+
+/*
+    Nothing changed since the last stamped build & its artifacts are all still there,
+    still linked to their stamped copies (arkham watch & plain make leave unstamped builds behind)
+*/
+pub fn is_up_to_date(context: &ProjectContext, info: &ProjectInfo, fingerprint: &str) -> bool {
+    info.build_fingerprint.as_deref() == Some(fingerprint)
+        && info.artifacts.iter().all(|artifact| {
+            let path = context.path(artifact);
+            path.is_symlink() && path.exists()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> (tempfile::TempDir, ProjectContext, ProjectInfo) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("main.c"), "int main() { return 0; }\n").unwrap();
+        fs::write(dir.path().join("Makefile"), "all: main\n").unwrap();
+        let context = ProjectContext::at(dir.path());
+        let info = ProjectInfo::new(
+            "main".to_string(),
+            "1.00".to_string(),
+            dir.path().to_string_lossy().to_string(),
+        );
+        (dir, context, info)
+    }
+
+    fn fingerprint(context: &ProjectContext, info: &ProjectInfo, args: &[&str]) -> String {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        build_fingerprint(context, info, BuildSystem::Make, &args).unwrap()
+    }
+
+    #[test]
+    fn same_tree_same_fingerprint() {
+        let (_dir, context, info) = project();
+        assert_eq!(
+            fingerprint(&context, &info, &["-j8"]),
+            fingerprint(&context, &info, &["-j8"])
+        );
+    }
+
+    #[test]
+    fn builds_and_state_files_dont_count() {
+        let (dir, context, info) = project();
+        let before = fingerprint(&context, &info, &[]);
+
+        fs::write(dir.path().join("main_v_1.00"), "binary").unwrap();
+        std::os::unix::fs::symlink("main_v_1.00", dir.path().join("main")).unwrap();
+        fs::write(dir.path().join(".version.info"), "project_name=main\n").unwrap();
+        assert_eq!(fingerprint(&context, &info, &[]), before);
+    }
+
+    #[test]
+    fn sources_and_args_count() {
+        let (dir, context, info) = project();
+        let before = fingerprint(&context, &info, &[]);
+        assert_ne!(fingerprint(&context, &info, &["V=1"]), before);
+
+        fs::write(dir.path().join("main.c"), "int main() { return 1; }\n").unwrap();
+        assert_ne!(fingerprint(&context, &info, &[]), before);
+    }
+}
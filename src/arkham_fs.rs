@@ -0,0 +1,769 @@
+/*
+ CRASH-SAFE STATE FILES : temp file + fsync + rename, whole-record appends
+    version updates are transactions with an undo journal on disk,
+    so a killed update is rolled back on the next run instead of left half-applied
+*/
+use crate::arkham_constants::{JOURNAL_FILE, TEMP_FILE_SUFFIX};
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_trace::{trace_event, trace_read, trace_write};
+use crate::arkham_version::read_version_info;
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+// <file>.<tag>.<pid>.arkham-tmp, next to the file so the rename never crosses filesystems
+pub fn temp_path(path: &Path, tag: &str) -> PathBuf {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(
+        "{}.{}.{}{}",
+        file_name,
+        tag,
+        std::process::id(),
+        TEMP_FILE_SUFFIX
+    ))
+}
+
+// Make the rename itself durable
+fn sync_parent_dir(path: &Path) -> Result<(), ArkhamError> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()?;
+    Ok(())
+}
+
+fn write_synced(path: &Path, contents: &[u8]) -> Result<(), ArkhamError> {
+    let mut file = File::create(path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+// Readers see either the old file or the new one, never half of it
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), ArkhamError> {
+    let temp = temp_path(path, "write");
+
+    let result = (|| -> Result<(), ArkhamError> {
+        write_synced(&temp, contents)?;
+        fs::rename(&temp, path)?;
+        sync_parent_dir(path)
+    })();
+    if result.is_ok() {
+        trace_write(path, "atomic", contents.len());
+    }
+
+    if result.is_err() && temp.exists() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+// One write() per record : a crash can only lose the record, not interleave half of it
+pub fn append_record(path: &Path, record: &str) -> Result<(), ArkhamError> {
+    let mut line = record.trim_end_matches('\n').to_string();
+    line.push('\n');
+
+    let mut file = OpenOptions::new().append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    file.sync_data()?;
+    trace_write(path, "append", line.len());
+    Ok(())
+}
+
+// pid of the arkham that left <file>.<tag>.<pid>.arkham-tmp behind, if it's still running
+fn writer_alive(temp_name: &str) -> bool {
+    let pid = temp_name
+        .trim_end_matches(TEMP_FILE_SUFFIX)
+        .rsplit('.')
+        .next()
+        .and_then(|pid| pid.parse::<u32>().ok());
+
+    match pid {
+        Some(pid) => pid != std::process::id() && Path::new(&format!("/proc/{}", pid)).exists(),
+        None => false,
+    }
+}
+
+// A log whose last byte isn't '\n' was cut mid-append : (bytes to keep, the partial record)
+fn torn_record(contents: &[u8]) -> Option<(usize, String)> {
+    if contents.is_empty() || contents.ends_with(b"\n") {
+        return None;
+    }
+
+    let keep = contents
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|pos| pos + 1)
+        .unwrap_or(0);
+    Some((keep, String::from_utf8_lossy(&contents[keep..]).to_string()))
+}
+
+// Drop the partial record
+fn trim_torn_record(log_path: &Path) -> Result<Option<String>, ArkhamError> {
+    if !log_path.is_file() {
+        return Ok(None);
+    }
+
+    let mut log_file = OpenOptions::new().read(true).write(true).open(log_path)?;
+    let mut contents = Vec::new();
+    log_file.read_to_end(&mut contents)?;
+
+    let Some((keep, torn)) = torn_record(&contents) else {
+        return Ok(None);
+    };
+    log_file.set_len(keep as u64)?;
+    log_file.sync_all()?;
+    trace_write(log_path, "truncate", keep);
+    Ok(Some(torn))
+}
+
+// How to put one step of a transaction back
+#[derive(Debug, PartialEq)]
+enum UndoStep {
+    Remove(PathBuf),                  // file that didn't exist before
+    Rename(PathBuf, PathBuf),         // move it back : (from, to)
+    Relink(PathBuf, Option<PathBuf>), // symlink & its previous target
+}
+
+impl UndoStep {
+    // One journal line : "rename<TAB>from<TAB>to", paths relative to the project root
+    fn to_journal_line(&self, root: &Path) -> String {
+        let relative = |path: &Path| {
+            path.strip_prefix(root)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+        match self {
+            UndoStep::Remove(path) => format!("remove\t{}", relative(path)),
+            UndoStep::Rename(from, to) => format!("rename\t{}\t{}", relative(from), relative(to)),
+            UndoStep::Relink(link, previous) => format!(
+                "relink\t{}\t{}",
+                relative(link),
+                previous
+                    .as_ref()
+                    .map(|target| target.display().to_string())
+                    .unwrap_or_default()
+            ),
+        }
+    }
+
+    fn from_journal_line(line: &str, root: &Path) -> Option<UndoStep> {
+        match line.split('\t').collect::<Vec<&str>>().as_slice() {
+            ["remove", path] => Some(UndoStep::Remove(root.join(path))),
+            ["rename", from, to] => Some(UndoStep::Rename(root.join(from), root.join(to))),
+            ["relink", link, ""] => Some(UndoStep::Relink(root.join(link), None)),
+            ["relink", link, target] => Some(UndoStep::Relink(
+                root.join(link),
+                Some(PathBuf::from(target)),
+            )),
+            _ => None,
+        }
+    }
+
+    // Journaled before the step ran : a step the crash came first has nothing to undo
+    fn undo(self) -> (PathBuf, Result<(), ArkhamError>) {
+        let present = |path: &Path| path.is_symlink() || path.exists();
+        match self {
+            UndoStep::Remove(path) => {
+                let result = match present(&path) {
+                    true => fs::remove_file(&path).map_err(ArkhamError::from),
+                    false => Ok(()),
+                };
+                (path, result)
+            }
+            UndoStep::Rename(from, to) => {
+                let result = match present(&from) {
+                    true => fs::rename(&from, &to).map_err(ArkhamError::from),
+                    false => Ok(()),
+                };
+                (to, result)
+            }
+            UndoStep::Relink(link, previous) => {
+                if present(&link) {
+                    if let Err(e) = fs::remove_file(&link) {
+                        return (link, Err(ArkhamError::from(e)));
+                    }
+                }
+                let result = match previous {
+                    Some(target) => {
+                        std::os::unix::fs::symlink(target, &link).map_err(ArkhamError::from)
+                    }
+                    None => Ok(()),
+                };
+                (link, result)
+            }
+        }
+    }
+}
+
+// Undo newest first; best effort, every step gets a try
+fn undo_all(steps: Vec<UndoStep>) {
+    trace_event("rollback", &[("steps", steps.len().to_string())]);
+    for step in steps.into_iter().rev() {
+        let (path, result) = step.undo();
+        if let Err(e) = result {
+            println!(
+                "Warning: rollback could not restore {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+/*
+    All-or-nothing file changes : every step records its undo, rollback() replays them backwards
+    Each undo is journaled (<root>/.arkham.journal.<pid>.arkham-tmp) before its step runs,
+    so a killed arkham's update can be rolled back by the next one (recover_interrupted_writes)
+    Replaced files are set aside (<file>.old.<pid>.arkham-tmp) & deleted on commit()
+*/
+pub struct FsTransaction {
+    root: PathBuf,
+    journal: PathBuf,
+    undo: Vec<UndoStep>,
+    discard: Vec<PathBuf>,
+}
+
+impl FsTransaction {
+    pub fn new(root: &Path) -> Self {
+        FsTransaction {
+            root: root.to_path_buf(),
+            journal: root.join(format!(
+                "{}.{}{}",
+                JOURNAL_FILE,
+                std::process::id(),
+                TEMP_FILE_SUFFIX
+            )),
+            undo: Vec::new(),
+            discard: Vec::new(),
+        }
+    }
+
+    // Write-ahead : the undo is on disk before the step touches anything
+    fn journal(&self, step: &UndoStep) -> Result<(), ArkhamError> {
+        let line = step.to_journal_line(&self.root);
+        if line.contains('\n') {
+            return Err(ArkhamError::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("can't journal a path with a line break: {}", line),
+            )));
+        }
+        if !self.journal.exists() {
+            File::create(&self.journal)?;
+            sync_parent_dir(&self.journal)?;
+        }
+        append_record(&self.journal, &line)
+    }
+
+    fn record(&mut self, step: UndoStep) -> Result<(), ArkhamError> {
+        self.journal(&step)?;
+        self.undo.push(step);
+        Ok(())
+    }
+
+    // A file this transaction creates (staged copies etc.) : recorded before it exists
+    pub fn track_new(&mut self, path: &Path) -> Result<(), ArkhamError> {
+        self.record(UndoStep::Remove(path.to_path_buf()))
+    }
+
+    pub fn rename(&mut self, from: &Path, to: &Path) -> Result<(), ArkhamError> {
+        let step = UndoStep::Rename(to.to_path_buf(), from.to_path_buf());
+        self.journal(&step)?;
+        fs::rename(from, to)?;
+        trace_event(
+            "rename",
+            &[
+                ("from", from.display().to_string()),
+                ("to", to.display().to_string()),
+            ],
+        );
+        self.undo.push(step);
+        Ok(())
+    }
+
+    // Keep the existing file under a second name until commit
+    // a hard link : path itself stays in place until something is renamed over it
+    fn set_aside(&mut self, path: &Path) -> Result<(), ArkhamError> {
+        let backup = temp_path(path, "old");
+        let step = UndoStep::Rename(backup.clone(), path.to_path_buf());
+        self.journal(&step)?;
+        if fs::hard_link(path, &backup).is_err() {
+            fs::rename(path, &backup)?;
+        }
+        self.undo.push(step);
+        self.discard.push(backup);
+        Ok(())
+    }
+
+    // Staged file takes its final name, whatever was there gets set aside
+    pub fn install(&mut self, staged: &Path, path: &Path) -> Result<(), ArkhamError> {
+        if path.exists() || path.is_symlink() {
+            self.set_aside(path)?;
+        }
+        self.rename(staged, path)
+    }
+
+    // Point link at target (a plain file sitting there gets set aside)
+    pub fn replace_symlink(&mut self, link: &Path, target: &Path) -> Result<(), ArkhamError> {
+        let previous = match fs::read_link(link) {
+            Ok(previous) => Some(previous),
+            Err(_) => {
+                if link.exists() {
+                    self.set_aside(link)?;
+                }
+                None
+            }
+        };
+        let step = UndoStep::Relink(link.to_path_buf(), previous);
+        self.journal(&step)?;
+
+        // new link under a temp name, renamed over the old one : never a moment without a link
+        let temp_link = temp_path(link, "link");
+        if temp_link.is_symlink() {
+            fs::remove_file(&temp_link)?;
+        }
+        std::os::unix::fs::symlink(target, &temp_link)?;
+        if let Err(e) = fs::rename(&temp_link, link) {
+            let _ = fs::remove_file(&temp_link);
+            return Err(ArkhamError::from(e));
+        }
+
+        trace_event(
+            "symlink",
+            &[
+                ("link", link.display().to_string()),
+                ("target", target.display().to_string()),
+            ],
+        );
+        self.undo.push(step);
+        Ok(())
+    }
+
+    // Like write_atomic : new contents staged & installed, the previous file set aside
+    pub fn write_file(&mut self, path: &Path, contents: &[u8]) -> Result<(), ArkhamError> {
+        let staged = temp_path(path, "new");
+        self.track_new(&staged)?;
+        write_synced(&staged, contents)?;
+        self.install(&staged, path)?;
+        trace_write(path, "transaction", contents.len());
+        Ok(())
+    }
+
+    // Keep everything : the journal going away is the commit point, set-asides go after it
+    pub fn commit(self) {
+        if fs::remove_file(&self.journal).is_ok() {
+            let _ = sync_parent_dir(&self.journal);
+        }
+        for backup in &self.discard {
+            if fs::remove_file(backup).is_ok() {
+                trace_event("remove", &[("file", backup.display().to_string())]);
+            }
+        }
+    }
+
+    pub fn rollback(self) {
+        undo_all(self.undo);
+        let _ = fs::remove_file(&self.journal);
+    }
+}
+
+// A dead arkham's journal, replayed backwards => its update never happened
+// a last line without '\n' was cut by the crash : its step never started
+fn roll_back_journal(root: &Path, journal: &Path) -> Result<usize, ArkhamError> {
+    trace_read(journal);
+    let contents = fs::read_to_string(journal)?;
+    let complete = match contents.rfind('\n') {
+        Some(end) => &contents[..end],
+        None => "",
+    };
+    let steps: Vec<UndoStep> = complete
+        .lines()
+        .filter_map(|line| UndoStep::from_journal_line(line, root))
+        .collect();
+    let count = steps.len();
+
+    undo_all(steps);
+    fs::remove_file(journal)?;
+    sync_parent_dir(journal)?;
+    Ok(count)
+}
+
+// Where temp files can be left : state file dirs, artifact dirs & prev_builds/
+fn recovery_dirs(context: &ProjectContext) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = vec![context.root.clone(), context.prev_builds_dir()];
+    let mut files = vec![context.info_file(), context.log_file()];
+    // unreadable info (old schema, corrupt ...) : the artifact dirs are just not known yet
+    if let Ok(Some(info)) = read_version_info(context) {
+        files.extend(info.artifacts.iter().map(|artifact| context.path(artifact)));
+    }
+
+    for file in files {
+        if let Some(parent) = file.parent() {
+            if !dirs.iter().any(|dir| dir == parent) {
+                dirs.push(parent.to_path_buf());
+            }
+        }
+    }
+    dirs
+}
+
+// <root>/.arkham.journal.<pid>.arkham-tmp of arkhams that are gone
+fn dead_journals(context: &ProjectContext) -> Result<Vec<PathBuf>, ArkhamError> {
+    let mut journals = Vec::new();
+    for entry in fs::read_dir(&context.root)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with(JOURNAL_FILE)
+            && file_name.ends_with(TEMP_FILE_SUFFIX)
+            && !writer_alive(&file_name)
+        {
+            journals.push(entry.path());
+        }
+    }
+    Ok(journals)
+}
+
+// Temp files (journals aside) of arkhams that are gone
+fn dead_temp_files(context: &ProjectContext) -> Result<Vec<PathBuf>, ArkhamError> {
+    let mut temp_files = Vec::new();
+    for dir in recovery_dirs(context) {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if file_name.ends_with(TEMP_FILE_SUFFIX)
+                && !file_name.starts_with(JOURNAL_FILE)
+                && !writer_alive(&file_name)
+            {
+                temp_files.push(entry.path());
+            }
+        }
+    }
+    Ok(temp_files)
+}
+
+/*
+    Startup of a writing command, under the project lock : clean up after an arkham that got killed mid-write
+    - journal left by a dead arkham => its version update is rolled back, set-aside files restored
+    - leftover temp files => staged copies that never got installed, or set-asides of an update
+      that committed (no journal left) : the real files are the good copies either way
+    - torn last log record => truncated back to the last complete one
+*/
+pub fn recover_interrupted_writes(context: &ProjectContext) -> Result<(), ArkhamError> {
+    for journal in dead_journals(context)? {
+        let steps = roll_back_journal(&context.root, &journal)?;
+        println!(
+            "Recovered from an interrupted version update: rolled back {} step(s) ({})",
+            steps,
+            journal.file_name().unwrap_or_default().to_string_lossy()
+        );
+    }
+
+    for temp_file in dead_temp_files(context)? {
+        fs::remove_file(&temp_file)?;
+        println!(
+            "Recovered from an interrupted write: discarded {}",
+            temp_file.display()
+        );
+    }
+
+    if let Some(torn) = trim_torn_record(&context.log_file())? {
+        println!(
+            "Recovered from an interrupted write: dropped incomplete record '{}' from {}",
+            torn,
+            context.log_file().display()
+        );
+    }
+
+    Ok(())
+}
+
+// Read-only commands : what recover_interrupted_writes would fix, nothing touched
+pub fn interrupted_writes(context: &ProjectContext) -> Result<Vec<String>, ArkhamError> {
+    let mut found: Vec<String> = Vec::new();
+    for journal in dead_journals(context)? {
+        found.push(format!(
+            "an interrupted version update is not rolled back yet ({})",
+            journal.display()
+        ));
+    }
+    for temp_file in dead_temp_files(context)? {
+        found.push(format!(
+            "{} is left over from an interrupted write",
+            temp_file.display()
+        ));
+    }
+
+    let log_path = context.log_file();
+    if log_path.is_file() {
+        trace_read(&log_path);
+        if let Some((_, torn)) = torn_record(&fs::read(&log_path)?) {
+            found.push(format!(
+                "{} ends in an incomplete record '{}'",
+                log_path.display(),
+                torn
+            ));
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    // Set for the child process of killed_update_is_rolled_back : the project it updates
+    const CRASH_ROOT_ENV: &str = "ARKHAM_TEST_CRASH_ROOT";
+
+    fn project_at_1_00() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(".version.info"), "current_version=1.00\n").unwrap();
+        fs::write(root.join("main_v_1.00"), "build 1.00").unwrap();
+        std::os::unix::fs::symlink("main_v_1.00", root.join("main")).unwrap();
+        fs::create_dir(root.join("prev_builds")).unwrap();
+        dir
+    }
+
+    fn temp_files(dir: &Path) -> Vec<String> {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(dir).unwrap().flatten() {
+            if entry.path().is_dir() {
+                found.extend(temp_files(&entry.path()));
+            } else if entry
+                .file_name()
+                .to_string_lossy()
+                .ends_with(TEMP_FILE_SUFFIX)
+            {
+                found.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        found
+    }
+
+    // Child half : the steps of a bump to 2.00, then killed before commit()
+    #[test]
+    #[ignore = "run by killed_update_is_rolled_back"]
+    fn update_then_die() {
+        let Ok(root) = std::env::var(CRASH_ROOT_ENV) else {
+            return;
+        };
+        let root = Path::new(&root);
+        let mut transaction = FsTransaction::new(root);
+
+        let versioned = root.join("main_v_2.00");
+        let staged = temp_path(&versioned, "new");
+        transaction.track_new(&staged).unwrap();
+        fs::write(&staged, "build 2.00").unwrap();
+        transaction.install(&staged, &versioned).unwrap();
+        transaction
+            .write_file(&root.join("main_v_2.00.sha256"), b"checksums")
+            .unwrap();
+        transaction
+            .replace_symlink(&root.join("main"), Path::new("main_v_2.00"))
+            .unwrap();
+        transaction
+            .write_file(&root.join(".version.info"), b"current_version=2.00\n")
+            .unwrap();
+        transaction
+            .rename(
+                &root.join("main_v_1.00"),
+                &root.join("prev_builds/main_v_1.00"),
+            )
+            .unwrap();
+
+        std::process::abort();
+    }
+
+    #[test]
+    fn killed_update_is_rolled_back() {
+        let dir = project_at_1_00();
+        let root = dir.path();
+
+        let status = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "arkham_fs::tests::update_then_die",
+                "--exact",
+                "--ignored",
+                "--test-threads=1",
+            ])
+            .env(CRASH_ROOT_ENV, root)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!status.success());
+
+        // half-applied : new info & symlink, the old info only in its set-aside
+        assert_eq!(
+            fs::read_to_string(root.join(".version.info")).unwrap(),
+            "current_version=2.00\n"
+        );
+        assert!(temp_files(root).iter().any(|name| name.contains(".old.")));
+        assert!(temp_files(root)
+            .iter()
+            .any(|name| name.starts_with(JOURNAL_FILE)));
+
+        recover_interrupted_writes(&ProjectContext::at(root)).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.join(".version.info")).unwrap(),
+            "current_version=1.00\n"
+        );
+        assert_eq!(
+            fs::read_link(root.join("main")).unwrap(),
+            PathBuf::from("main_v_1.00")
+        );
+        assert_eq!(fs::read_to_string(root.join("main")).unwrap(), "build 1.00");
+        assert!(!root.join("main_v_2.00").exists());
+        assert!(!root.join("main_v_2.00.sha256").exists());
+        assert!(!root.join("prev_builds/main_v_1.00").exists());
+        assert_eq!(temp_files(root), Vec::<String>::new());
+    }
+
+    #[test]
+    fn committed_update_leaves_no_temp_files() {
+        let dir = project_at_1_00();
+        let root = dir.path();
+
+        let mut transaction = FsTransaction::new(root);
+        transaction
+            .write_file(&root.join(".version.info"), b"current_version=2.00\n")
+            .unwrap();
+        transaction
+            .replace_symlink(&root.join("main"), Path::new("main_v_1.00"))
+            .unwrap();
+        transaction.commit();
+
+        assert_eq!(
+            fs::read_to_string(root.join(".version.info")).unwrap(),
+            "current_version=2.00\n"
+        );
+        assert_eq!(temp_files(root), Vec::<String>::new());
+    }
+
+    #[test]
+    fn journal_lines_round_trip() {
+        let root = Path::new("/project");
+        for step in [
+            UndoStep::Remove(root.join("main_v_2.00.new.1.arkham-tmp")),
+            UndoStep::Rename(
+                root.join("prev_builds/main_v_1.00"),
+                root.join("main_v_1.00"),
+            ),
+            UndoStep::Relink(
+                root.join("lib/libfoo.so"),
+                Some(PathBuf::from("libfoo.so_v_1.00")),
+            ),
+            UndoStep::Relink(root.join("main"), None),
+        ] {
+            let line = step.to_journal_line(root);
+            assert!(!line.contains("/project"));
+            assert_eq!(UndoStep::from_journal_line(&line, root), Some(step));
+        }
+    }
+
+    #[test]
+    fn torn_log_record_is_reported_then_dropped() {
+        let dir = project_at_1_00();
+        let context = ProjectContext::at(dir.path());
+        let log = "version_name,version_log\n1.00,\"first\"\n1.01,\"seco";
+        fs::write(context.log_file(), log).unwrap();
+
+        // read-only : reported, nothing touched
+        let found = interrupted_writes(&context).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].contains("1.01,\"seco"), "{}", found[0]);
+        assert_eq!(fs::read_to_string(context.log_file()).unwrap(), log);
+
+        recover_interrupted_writes(&context).unwrap();
+        assert_eq!(
+            fs::read_to_string(context.log_file()).unwrap(),
+            "version_name,version_log\n1.00,\"first\"\n"
+        );
+        assert!(interrupted_writes(&context).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_temp_files_of_dead_writers_are_discarded() {
+        let dir = project_at_1_00();
+        let root = dir.path();
+        // pid 1 is always there, no pid gets this high
+        let live = root.join(format!(".version.info.write.1{}", TEMP_FILE_SUFFIX));
+        let dead = root.join(format!(
+            "prev_builds/main_v_1.00.new.999999999{}",
+            TEMP_FILE_SUFFIX
+        ));
+        fs::write(&live, "being written").unwrap();
+        fs::write(&dead, "never installed").unwrap();
+
+        let context = ProjectContext::at(root);
+        assert_eq!(interrupted_writes(&context).unwrap().len(), 1);
+        recover_interrupted_writes(&context).unwrap();
+        assert!(live.exists());
+        assert!(!dead.exists());
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_whole_file() {
+        let dir = project_at_1_00();
+        let info = dir.path().join(".version.info");
+        write_atomic(&info, b"current_version=1.01\n").unwrap();
+        assert_eq!(fs::read_to_string(&info).unwrap(), "current_version=1.01\n");
+        assert_eq!(temp_files(dir.path()), Vec::<String>::new());
+
+        // append_record adds the line break a record is missing, never a second one
+        let log = dir.path().join(".version.log");
+        fs::write(&log, "header\n").unwrap();
+        append_record(&log, "1.00,a").unwrap();
+        append_record(&log, "1.01,b\n").unwrap();
+        assert_eq!(
+            fs::read_to_string(&log).unwrap(),
+            "header\n1.00,a\n1.01,b\n"
+        );
+    }
+
+    #[test]
+    fn rollback_puts_every_step_back() {
+        let dir = project_at_1_00();
+        let root = dir.path();
+
+        let mut transaction = FsTransaction::new(root);
+        let versioned = root.join("main_v_2.00");
+        let staged = temp_path(&versioned, "new");
+        transaction.track_new(&staged).unwrap();
+        fs::write(&staged, "build 2.00").unwrap();
+        transaction.install(&staged, &versioned).unwrap();
+        transaction
+            .replace_symlink(&root.join("main"), Path::new("main_v_2.00"))
+            .unwrap();
+        transaction
+            .write_file(&root.join(".version.info"), b"current_version=2.00\n")
+            .unwrap();
+        transaction
+            .rename(
+                &root.join("main_v_1.00"),
+                &root.join("prev_builds/main_v_1.00"),
+            )
+            .unwrap();
+        transaction.rollback();
+
+        assert_eq!(
+            fs::read_to_string(root.join(".version.info")).unwrap(),
+            "current_version=1.00\n"
+        );
+        assert_eq!(fs::read_to_string(root.join("main")).unwrap(), "build 1.00");
+        assert!(!versioned.exists());
+        assert!(!root.join("prev_builds/main_v_1.00").exists());
+        assert_eq!(temp_files(root), Vec::<String>::new());
+    }
+}
@@ -1,97 +1,522 @@
+use crate::arkham_config::{read_config, BackupBackend, Hook};
+use crate::arkham_constants::{ARKHAM_PROTECTED_FILES, ARKHAM_SCRATCH_FILES};
+use crate::arkham_dryrun::DryRun;
+use crate::arkham_editor::compose_log_message;
 use crate::arkham_errors::ArkhamError;
-use crate::arkham_utility::{display_header_msg, get_user_input};
-use crate::arkham_version::{log_version, read_version_info};
-use std::path::Path;
+use crate::arkham_hooks::{plan_hooks, run_hooks, HookEnv};
+use crate::arkham_project::ProjectContext;
+use crate::arkham_snapshot::{
+    create_snapshot, extract_snapshot, is_snapshot_id, new_snapshot_id, restore_snapshot,
+    snapshot_files, snapshot_listing, snapshot_path, IgnoreList,
+};
+use crate::arkham_trace::{run_output, run_status};
+use crate::arkham_utility::{display_header_msg, display_success_msg, get_user_input};
+use crate::arkham_version::{find_commit_id, log_version, plan_log_version, read_version_info};
+use crate::arkham_workspace::tree_artifacts;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-fn check_and_init_git(project_root: &str) -> Result<(), ArkhamError> {
-    let git_dir = Path::new(project_root).join(".git");
+fn check_and_init_git(project_root: &Path) -> Result<(), ArkhamError> {
+    let git_dir = project_root.join(".git");
 
     if !git_dir.exists() {
-        println!("Git Repo not found in {}, initializing..", project_root);
-        Command::new("git")
-            .current_dir(project_root)
-            .arg("init")
-            .output()
-            .map_err(|e| {
-                ArkhamError::BackupError(format!("Failed to initialize git repo: {}", e))
-            })?;
+        println!(
+            "Git Repo not found in {}, initializing..",
+            project_root.display()
+        );
+        run_output(Command::new("git").current_dir(project_root).arg("init")).map_err(|e| {
+            ArkhamError::BackupError(format!("Failed to initialize git repo: {}", e))
+        })?;
     }
     Ok(())
 }
 
-fn get_commit_id(project_root: &str) -> Result<String, ArkhamError> {
-    let cid_output = Command::new("git")
-        .current_dir(project_root)
-        .args(&["rev-parse", "HEAD"])
-        .output()
-        .map_err(|e| ArkhamError::BackupError(format!("Failed to get commit ID: {}", e)))?;
+// Scratch files listed in the repo's info/exclude : never staged by git add, no noise in git status
+fn exclude_scratch_files(project_root: &Path) -> Result<(), ArkhamError> {
+    let git_path = run_output(Command::new("git").current_dir(project_root).args([
+        "rev-parse",
+        "--git-path",
+        "info/exclude",
+    ]))
+    .map_err(|e| ArkhamError::BackupError(format!("Failed to locate the git dir: {}", e)))?;
+    if !git_path.status.success() {
+        return Err(ArkhamError::BackupError(format!(
+            "Failed to locate the git dir: {}",
+            String::from_utf8_lossy(&git_path.stderr).trim()
+        )));
+    }
+
+    let exclude_path = project_root.join(String::from_utf8_lossy(&git_path.stdout).trim());
+    let existing = fs::read_to_string(&exclude_path).unwrap_or_default();
+    let missing: Vec<&str> = ARKHAM_SCRATCH_FILES
+        .iter()
+        .copied()
+        .filter(|file| !existing.lines().any(|line| line.trim() == *file))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(info_dir) = exclude_path.parent() {
+        fs::create_dir_all(info_dir)?;
+    }
+    let mut contents = existing;
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    for file in missing {
+        // any depth : temp files sit next to the artifacts too
+        contents.push_str(&format!("{}\n", file));
+    }
+    fs::write(&exclude_path, contents)?;
+    Ok(())
+}
+
+fn get_commit_id(project_root: &Path) -> Result<String, ArkhamError> {
+    let cid_output = run_output(
+        Command::new("git")
+            .current_dir(project_root)
+            .args(["rev-parse", "HEAD"]),
+    )
+    .map_err(|e| ArkhamError::BackupError(format!("Failed to get commit ID: {}", e)))?;
+
+    // no commit yet (e.g. git identity not configured)
+    if !cid_output.status.success() {
+        return Err(ArkhamError::BackupError(format!(
+            "Failed to get commit ID: {}",
+            String::from_utf8_lossy(&cid_output.stderr).trim()
+        )));
+    }
 
     String::from_utf8(cid_output.stdout)
         .map(|s| s.trim().to_string())
         .map_err(|_| ArkhamError::BackupError("Invalid UTF-8 in commit ID".to_string()))
 }
 
-pub(crate) fn save_state() -> Result<(), ArkhamError> {
+// Scratch files an older arkham did commit, out of the index (kept on disk)
+fn untrack_args() -> Vec<String> {
+    let mut args: Vec<String> = ["rm", "-r", "--cached", "--quiet", "--ignore-unmatch", "--"]
+        .map(String::from)
+        .to_vec();
+    args.extend(ARKHAM_SCRATCH_FILES.iter().map(|file| file.to_string()));
+    args
+}
+
+// Pathspecs keeping arkham's own files out of checkout/diff
+fn protected_pathspecs() -> Vec<String> {
+    ARKHAM_PROTECTED_FILES
+        .iter()
+        .map(|file| format!(":(exclude){}", file))
+        .collect()
+}
+
+pub fn save_state(
+    context: &ProjectContext,
+    backend_override: Option<BackupBackend>,
+    edit: bool,
+) -> Result<(), ArkhamError> {
     display_header_msg("Saving Current Project State!");
 
     // read current version info
-    let version_info = read_version_info()?;
-
-    let current_version = match version_info {
-        Some(ref version) => &version.current_version,
-        None => {
-            return Err(ArkhamError::BackupError(
-                "Current Version not found!".to_string(),
-            ))
-        }
+    let version_info = read_version_info(context)?.ok_or(ArkhamError::BackupError(
+        "Current Version not found!".to_string(),
+    ))?;
+    let current_version = &version_info.current_version;
+    let project_root = context.root.as_path();
+
+    // CLI flag wins over .arkham.conf
+    let backend = match backend_override {
+        Some(backend) => backend,
+        None => read_config(project_root)?.backup_backend,
     };
 
-    let project_root = match version_info {
-        Some(ref version) => &version.project_root,
-        None => {
-            return Err(ArkhamError::BackupError(
-                "Project Root not found!".to_string(),
-            ))
+    // pre_backup : a failing hook stops us before anything is staged or archived
+    run_hooks(
+        context,
+        &version_info,
+        Hook::PreBackup,
+        &HookEnv::at(current_version),
+    )?;
+
+    if backend == BackupBackend::Git {
+        // Checking Git Repo
+        check_and_init_git(project_root)?;
+        exclude_scratch_files(project_root)?;
+
+        // Nothing changed : a commit would fail & HEAD is an older version's state
+        let status = run_output(
+            Command::new("git")
+                .current_dir(project_root)
+                .args(["status", "--porcelain"]),
+        )
+        .map_err(|e| ArkhamError::BackupError(format!("Failed to run git status: {}", e)))?;
+        if status.status.success() && status.stdout.iter().all(u8::is_ascii_whitespace) {
+            return Err(ArkhamError::BackupError(format!(
+                "nothing to commit: no changes since the last commit, v{} not logged",
+                current_version
+            )));
         }
+    }
+
+    // Log Message : asked before anything is staged, an aborted message leaves the index alone
+    let built_by = get_user_input("Who's building it? : ")?;
+    let commit_log = compose_log_message(context, &version_info, edit)?;
+
+    if backend == BackupBackend::Git {
+        // Staging and Commiting all files
+        let add_args = ["add", "."].map(String::from).to_vec();
+        for args in [untrack_args(), add_args] {
+            let stage = run_output(Command::new("git").current_dir(project_root).args(&args))
+                .map_err(|e| ArkhamError::BackupError(format!("Failed to stage files: {}", e)))?;
+            if !stage.status.success() {
+                return Err(ArkhamError::BackupError(format!(
+                    "git {} failed: {}",
+                    args[0],
+                    String::from_utf8_lossy(&stage.stderr).trim()
+                )));
+            }
+        }
+    }
+
+    let commit_id = match backend {
+        BackupBackend::Git => {
+            // commiting
+            let commit = run_output(Command::new("git").current_dir(project_root).args([
+                "commit",
+                "-m",
+                &format!("v_{}", current_version),
+            ]))
+            .map_err(|e| ArkhamError::BackupError(format!("Failed to commit changes: {}", e)))?;
+
+            // no commit => HEAD is still the previous one, never log it for this version
+            if !commit.status.success() {
+                let stdout = String::from_utf8_lossy(&commit.stdout);
+                let reason = match stdout.contains("nothing to commit") {
+                    true => "nothing to commit".to_string(),
+                    false => String::from_utf8_lossy(&commit.stderr).trim().to_string(),
+                };
+                return Err(ArkhamError::BackupError(format!(
+                    "git commit failed: {}, v{} not logged",
+                    reason, current_version
+                )));
+            }
+
+            // fetch commit id
+            get_commit_id(project_root)?
+        }
+        BackupBackend::Tarball => create_snapshot(
+            project_root,
+            &tree_artifacts(context, &version_info)?,
+            current_version,
+        )?,
+    };
+    log_version(context, &commit_log, &built_by, &commit_id)?;
+
+    display_success_msg(&format!(
+        "Successfully Saved state for version {}",
+        current_version
+    ));
+
+    let saved_env = HookEnv {
+        commit_id,
+        ..HookEnv::at(current_version)
     };
+    run_hooks(context, &version_info, Hook::PostBackup, &saved_env)
+}
+
+// arkham backup --dry-run : no prompts, commands & files only
+pub fn plan_backup(
+    context: &ProjectContext,
+    backend_override: Option<BackupBackend>,
+) -> Result<(), ArkhamError> {
+    let version_info = read_version_info(context)?.ok_or(ArkhamError::BackupError(
+        "Current Version not found!".to_string(),
+    ))?;
+    let current_version = &version_info.current_version;
+    let project_root = context.root.as_path();
 
-    // Checking Git Repo
-    check_and_init_git(project_root)?;
+    let backend = match backend_override {
+        Some(backend) => backend,
+        None => read_config(project_root)?.backup_backend,
+    };
 
-    // Staging and Commiting all files
-    Command::new("git")
-        .current_dir(project_root)
-        .args(&["add", "."])
-        .output()
-        .map_err(|e| ArkhamError::BackupError(format!("Failed to stage files: {}", e)))?;
+    let mut plan = DryRun::new(context);
+    plan_hooks(
+        context,
+        &version_info,
+        Hook::PreBackup,
+        &HookEnv::at(current_version),
+        &mut plan,
+    )?;
+    plan.note("asks who's building it & for a message ($VISUAL / $EDITOR unless --no-edit)");
+    let state_id = match backend {
+        BackupBackend::Git => {
+            let git = || {
+                let mut cmd = Command::new("git");
+                cmd.current_dir(project_root);
+                cmd
+            };
+            if !project_root.join(".git").exists() {
+                plan.run(git().arg("init"));
+            }
+            plan.note("lists arkham's scratch files in .git/info/exclude");
+            plan.run(git().args(untrack_args()));
+            plan.run(git().args(["add", "."]));
+            plan.run(git().args(["commit", "-m", &format!("v_{}", current_version)]));
+            plan.run(git().args(["rev-parse", "HEAD"]));
+            "<commit id>".to_string()
+        }
+        BackupBackend::Tarball => {
+            let snapshot_id = new_snapshot_id(current_version);
+            let files = snapshot_files(project_root, &tree_artifacts(context, &version_info)?)?;
+            plan.create(
+                &snapshot_path(project_root, &snapshot_id),
+                &format!("{} files", files.len()),
+            );
+            snapshot_id
+        }
+    };
+    plan_log_version(context, &version_info, &state_id, &mut plan);
 
-    // Log Message
-    let built_by = get_user_input("Who's building it? : ")?;
-    let commit_log = get_user_input(
-        "Enter a commit message describing the changes [ DON'T ADD a ',' in the message ] : ",
+    let saved_env = HookEnv {
+        commit_id: state_id,
+        ..HookEnv::at(current_version)
+    };
+    plan_hooks(
+        context,
+        &version_info,
+        Hook::PostBackup,
+        &saved_env,
+        &mut plan,
     )?;
 
-    // commiting
-    Command::new("git")
-        .current_dir(project_root)
-        .args(&["commit", "-m", &format!("v_{}", current_version)])
-        .output()
-        .map_err(|e| ArkhamError::BackupError(format!("Failed to commit changes: {}", e)))?;
+    plan.print(&format!("arkham backup (v{})", current_version));
+    Ok(())
+}
 
-    // fetch commit id
-    let commit_id = get_commit_id(project_root)?;
-    log_version(&commit_log, &built_by, &commit_id)?;
+// Rollback : works for both git commits and tarball snapshots
+pub fn restore_to_state(context: &ProjectContext, version: &str) -> Result<(), ArkhamError> {
+    let info = read_version_info(context)?.ok_or(ArkhamError::MissingVersionInfo)?;
+    let state_id = find_commit_id(context, version)?;
 
     display_header_msg(&format!(
-        "Successfully Saved state for version {}",
-        current_version
+        "Restoring Project State: v{}\n{}",
+        version, state_id
     ));
+
+    let confirm = get_user_input(match is_snapshot_id(&state_id) {
+        true => "Project files will be overwritten with this version, files added since removed. Continue? (yes [y] | no [n]) = ",
+        false => "Project files will be overwritten with this version. Continue? (yes [y] | no [n]) = ",
+    })?;
+    if !(confirm.eq_ignore_ascii_case("yes") || confirm.eq_ignore_ascii_case("y")) {
+        println!("Restore cancelled.");
+        return Ok(());
+    }
+
+    if is_snapshot_id(&state_id) {
+        restore_snapshot(&context.root, &tree_artifacts(context, &info)?, &state_id)?;
+    } else {
+        let checkout = run_output(
+            Command::new("git")
+                .current_dir(&context.root)
+                .args(["checkout", &state_id, "--", "."])
+                .args(protected_pathspecs()),
+        )
+        .map_err(|e| ArkhamError::BackupError(format!("Failed to run git checkout: {}", e)))?;
+
+        if !checkout.status.success() {
+            return Err(ArkhamError::BackupError(format!(
+                "git checkout {} failed: {}",
+                state_id,
+                String::from_utf8_lossy(&checkout.stderr).trim()
+            )));
+        }
+    }
+
+    display_success_msg(&format!("Project restored to version {}", version));
     Ok(())
 }
 
+// Dump a saved state (commit or snapshot) into a directory
+fn materialize_state(
+    project_root: &Path,
+    state_id: &str,
+    destination: &Path,
+) -> Result<(), ArkhamError> {
+    fs::create_dir_all(destination)?;
+
+    if is_snapshot_id(state_id) {
+        return extract_snapshot(project_root, state_id, destination);
+    }
+
+    let archive = run_output(Command::new("git").current_dir(project_root).args([
+        "archive",
+        "--format=tar",
+        state_id,
+    ]))
+    .map_err(|e| ArkhamError::BackupError(format!("Failed to run git archive: {}", e)))?;
+
+    if !archive.status.success() {
+        return Err(ArkhamError::BackupError(format!(
+            "git archive {} failed: {}",
+            state_id,
+            String::from_utf8_lossy(&archive.stderr).trim()
+        )));
+    }
+
+    tar::Archive::new(archive.stdout.as_slice()).unpack(destination)?;
+    Ok(())
+}
+
+// "M  src/main.c" lines out of git's --name-status (renames as "R  old -> new"), ignored files left out
+fn name_status_lines(output: &str, ignore: &IgnoreList) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\t');
+            let status = parts.next()?.chars().next()?;
+            let paths: Vec<&str> = parts.collect();
+            if paths.iter().all(|path| ignore.is_ignored(Path::new(path))) {
+                return None;
+            }
+            Some(format!("{}  {}", status, paths.join(" -> ")))
+        })
+        .collect()
+}
+
+// Files changed from one saved state to the next (no previous state => all of its files, as added)
+pub fn changed_files(
+    context: &ProjectContext,
+    from: Option<&str>,
+    to: &str,
+) -> Result<Vec<String>, ArkhamError> {
+    if from.is_some_and(|from| is_snapshot_id(from) != is_snapshot_id(to)) {
+        return Err(ArkhamError::BackupError(
+            "saved with git on one side & as a snapshot on the other, no file list across backends"
+                .to_string(),
+        ));
+    }
+
+    if !is_snapshot_id(to) {
+        // git's empty tree : diffing against it lists every file of the first state
+        let from = from.unwrap_or("4b825dc642cb6eb9a060e54bf8d69288fbee4904");
+        let diff = run_output(
+            Command::new("git")
+                .current_dir(&context.root)
+                .args(["diff", "--name-status", from, to, "--", "."])
+                .args(protected_pathspecs()),
+        )
+        .map_err(|e| ArkhamError::BackupError(format!("Failed to run git diff: {}", e)))?;
 
-// Git Rollback
-pub(crate) fn _restore_to_state(_version: &str) -> Result<bool, ArkhamError> {
-    unimplemented!("To be Done!");
+        if !diff.status.success() {
+            return Err(ArkhamError::BackupError(format!(
+                "git diff {} {} failed: {}",
+                from,
+                to,
+                String::from_utf8_lossy(&diff.stderr).trim()
+            )));
+        }
+        let info = read_version_info(context)?.ok_or(ArkhamError::MissingVersionInfo)?;
+        let ignore = IgnoreList::load(&context.root, &tree_artifacts(context, &info)?)?;
+        return Ok(name_status_lines(
+            &String::from_utf8_lossy(&diff.stdout),
+            &ignore,
+        ));
+    }
+
+    // Snapshots : compare the listings, same size & mtime => unchanged
+    let before = match from {
+        Some(from) => snapshot_listing(&context.root, from)?,
+        None => Default::default(),
+    };
+    let after = snapshot_listing(&context.root, to)?;
+    let mut changes = Vec::new();
+    for (path, meta) in &after {
+        match before.get(path) {
+            None => changes.push(format!("A  {}", path.display())),
+            Some(old) if old != meta => changes.push(format!("M  {}", path.display())),
+            _ => {}
+        }
+    }
+    for path in before.keys().filter(|path| !after.contains_key(*path)) {
+        changes.push(format!("D  {}", path.display()));
+    }
+    changes.sort_by(|a, b| a[3..].cmp(&b[3..]));
+    Ok(changes)
+}
+
+// arkham diff <version> [version] : one version => against the working tree
+pub fn diff_states(context: &ProjectContext, versions: &[String]) -> Result<(), ArkhamError> {
+    if versions.is_empty() {
+        return Err(ArkhamError::NoVersionSpecified);
+    }
+    if versions.len() > 2 {
+        return Err(ArkhamError::BackupError(
+            "diff takes one or two versions".to_string(),
+        ));
+    }
+
+    let info = read_version_info(context)?.ok_or(ArkhamError::MissingVersionInfo)?;
+    let state_ids = versions
+        .iter()
+        .map(|ver| find_commit_id(context, ver))
+        .collect::<Result<Vec<String>, ArkhamError>>()?;
+
+    display_header_msg(&format!(
+        "Diff: v{} -> {}",
+        versions[0],
+        versions
+            .get(1)
+            .map(|ver| format!("v{}", ver))
+            .unwrap_or("working tree".to_string())
+    ));
+
+    // Both sides in git : let git do it
+    if state_ids.iter().all(|id| !is_snapshot_id(id)) {
+        run_status(
+            Command::new("git")
+                .current_dir(&context.root)
+                .arg("diff")
+                .args(&state_ids)
+                .args(["--", "."])
+                .args(protected_pathspecs()),
+        )
+        .map_err(|e| ArkhamError::BackupError(format!("Failed to run git diff: {}", e)))?;
+        return Ok(());
+    }
+
+    // Otherwise extract every saved side into a scratch dir and diff the trees
+    // (removed when dropped, error paths included)
+    let scratch_dir = tempfile::Builder::new().prefix("arkham-diff-").tempdir()?;
+    let mut sides: Vec<PathBuf> = Vec::new();
+    for (ver, state_id) in versions.iter().zip(&state_ids) {
+        let side = scratch_dir.path().join(format!("v_{}", ver));
+        materialize_state(&context.root, state_id, &side)?;
+        sides.push(side);
+    }
+    if sides.len() == 1 {
+        sides.push(context.root.clone());
+    }
+
+    let ignore = IgnoreList::load(&context.root, &tree_artifacts(context, &info)?)?;
+    let mut diff_cmd = Command::new("diff");
+    diff_cmd.arg("-ruN");
+    for exclude in ignore.diff_excludes() {
+        diff_cmd.arg("-x").arg(exclude);
+    }
+    let diff_status = run_status(diff_cmd.args(&sides));
+    scratch_dir.close()?;
+
+    // diff exits 1 when the trees differ, 2 on trouble
+    match diff_status {
+        Ok(status) if status.code() == Some(2) => Err(ArkhamError::BackupError(
+            "diff failed to compare the saved states".to_string(),
+        )),
+        Ok(_) => Ok(()),
+        Err(e) => Err(ArkhamError::BackupError(format!(
+            "Failed to run diff: {}",
+            e
+        ))),
+    }
 }
@@ -0,0 +1,136 @@
+/*
+ LIFECYCLE HOOKS : hook.<stage>=<command> lines in .arkham.conf
+    run with sh -c from the project root, what's going on passed as ARKHAM_* env vars
+    pre_* failing => the operation stops, post_* failing => reported once the operation is done
+*/
+use crate::arkham_config::{read_config, Hook};
+use crate::arkham_constants::{LOCK_FILE, LOCK_HELD_ENV};
+use crate::arkham_dryrun::DryRun;
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_trace::run_status;
+use crate::arkham_version::ProjectInfo;
+
+use std::process::Command;
+
+// Versions & state ID a hook sees (empty when not known yet)
+#[derive(Default)]
+pub struct HookEnv {
+    pub old_version: String,
+    pub new_version: String,
+    pub commit_id: String,
+}
+
+impl HookEnv {
+    // Same version before & after : build & backup
+    pub fn at(version: &str) -> Self {
+        HookEnv {
+            old_version: version.to_string(),
+            new_version: version.to_string(),
+            commit_id: String::new(),
+        }
+    }
+}
+
+fn operation(hook: Hook) -> &'static str {
+    match hook {
+        Hook::PreBuild | Hook::PostBuild => "build",
+        Hook::PreBump | Hook::PostBump => "version bump",
+        Hook::PreBackup | Hook::PostBackup => "backup",
+    }
+}
+
+/*
+    ARKHAM_HOOK, ARKHAM_PROJECT, ARKHAM_ROOT, ARKHAM_COMPONENT, ARKHAM_OLD_VERSION, ARKHAM_NEW_VERSION,
+    ARKHAM_ARTIFACT (the executable), ARKHAM_ARTIFACTS (every artifact, ':' separated), ARKHAM_COMMIT_ID
+*/
+fn hook_command(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    hook: Hook,
+    command: &str,
+    env: &HookEnv,
+) -> Command {
+    let artifacts: Vec<String> = info
+        .artifacts
+        .iter()
+        .map(|artifact| context.path(artifact).display().to_string())
+        .collect();
+
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .current_dir(&context.root)
+        .env("ARKHAM_HOOK", hook.name())
+        .env("ARKHAM_PROJECT", &info.project_name)
+        .env("ARKHAM_ROOT", &context.root)
+        .env(
+            "ARKHAM_COMPONENT",
+            context.component.as_ref().map_or("", |c| c.name.as_str()),
+        )
+        .env("ARKHAM_OLD_VERSION", &env.old_version)
+        .env("ARKHAM_NEW_VERSION", &env.new_version)
+        .env(
+            "ARKHAM_ARTIFACT",
+            context.path(info.artifacts.first().unwrap_or(&info.project_name)),
+        )
+        .env("ARKHAM_ARTIFACTS", artifacts.join(":"))
+        .env("ARKHAM_COMMIT_ID", &env.commit_id)
+        // an arkham started by the hook shares our project lock instead of waiting on it
+        .env(LOCK_HELD_ENV, context.path(LOCK_FILE));
+    cmd
+}
+
+// Every command configured for this stage, in order; the first failure stops the rest
+pub fn run_hooks(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    hook: Hook,
+    env: &HookEnv,
+) -> Result<(), ArkhamError> {
+    let config = read_config(&context.root)?;
+
+    for command in config.hooks_for(hook) {
+        println!("Running {} hook: {}", hook.name(), command);
+        let status =
+            run_status(&mut hook_command(context, info, hook, command, env)).map_err(|e| {
+                ArkhamError::HookError(format!("{} hook '{}': {}", hook.name(), command, e))
+            })?;
+
+        if !status.success() {
+            let exit = status
+                .code()
+                .map(|code| format!("exit code {}", code))
+                .unwrap_or("a signal".to_string());
+            let outcome = match hook {
+                _ if hook.is_pre() => format!("{} aborted", operation(hook)),
+                Hook::PostBuild => "built, but the version was left alone".to_string(),
+                _ => format!("the {} itself went through", operation(hook)),
+            };
+            return Err(ArkhamError::HookError(format!(
+                "{} hook '{}' failed with {}, {}",
+                hook.name(),
+                command,
+                exit,
+                outcome
+            )));
+        }
+    }
+    Ok(())
+}
+
+// --dry-run : the hook commands that would run
+pub fn plan_hooks(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    hook: Hook,
+    env: &HookEnv,
+    plan: &mut DryRun,
+) -> Result<(), ArkhamError> {
+    let config = read_config(&context.root)?;
+    for command in config.hooks_for(hook) {
+        plan.note(&format!("{} hook:", hook.name()));
+        plan.run(&hook_command(context, info, hook, command, env));
+    }
+    Ok(())
+}
@@ -0,0 +1,225 @@
+/*
+ PROJECT SETUP : arkham init
+*/
+use crate::arkham_config::{read_config, write_config, ArkhamConfig, BackupBackend, BuildSystem};
+use crate::arkham_constants::CONFIG_FILE;
+use crate::arkham_errors::{validate_version, ArkhamError};
+use crate::arkham_project::ProjectContext;
+use crate::arkham_utility::{display_success_msg, get_user_input};
+use crate::arkham_version::{write_version_info, ProjectInfo};
+
+use std::path::PathBuf;
+
+// Wrong answers allowed per prompt before giving up
+const MAX_PROMPT_ATTEMPTS: usize = 3;
+
+// Anything left as None is asked for (or detected)
+#[derive(Debug, Default)]
+pub struct InitOptions {
+    pub project_name: Option<String>,
+    pub version: Option<String>,
+    pub project_root: Option<String>,
+    pub build_system: Option<BuildSystem>,
+    pub backup_backend: Option<BackupBackend>,
+    pub extra_artifacts: Vec<String>,
+    pub write_config: Option<bool>,
+    pub force: bool,
+    pub context: Option<ProjectContext>, // workspace component : root & state files already known
+}
+
+// Executable names end up in file names, symlinks & the CSV log
+pub fn validate_project_name(name: &str) -> Result<(), ArkhamError> {
+    if name.is_empty() {
+        return Err(ArkhamError::InitError(
+            "Executable name can't be empty".to_string(),
+        ));
+    }
+
+    if name
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, '/' | ',' | '=' | '"'))
+    {
+        return Err(ArkhamError::InitError(format!(
+            "Invalid executable name '{}': no spaces, '/', ',', '=' or '\"' allowed",
+            name
+        )));
+    }
+    Ok(())
+}
+
+// Extra artifacts : paths inside the project root
+pub fn validate_artifact_path(artifact: &str) -> Result<(), ArkhamError> {
+    let path = std::path::Path::new(artifact);
+
+    if artifact.is_empty()
+        || path.is_absolute()
+        || path.components().any(|c| c.as_os_str() == "..")
+        || artifact.contains(',')
+        || artifact.contains('=')
+    {
+        return Err(ArkhamError::InitError(format!(
+            "Invalid artifact '{}': must be a relative path inside the project (no '..', ',' or '=')",
+            artifact
+        )));
+    }
+    Ok(())
+}
+
+// Ask until the validator is happy
+fn prompt_until_valid(
+    prompt: &str,
+    validate: fn(&str) -> Result<(), ArkhamError>,
+) -> Result<String, ArkhamError> {
+    for _ in 0..MAX_PROMPT_ATTEMPTS {
+        let input = get_user_input(prompt)?;
+        match validate(&input) {
+            Ok(_) => return Ok(input),
+            Err(e) => println!("{}", e),
+        }
+    }
+
+    Err(ArkhamError::InitError(format!(
+        "No valid input after {} attempts",
+        MAX_PROMPT_ATTEMPTS
+    )))
+}
+
+// Nearest directory (cwd or above) holding a build file
+fn detect_project_root() -> Result<PathBuf, ArkhamError> {
+    let current_dir = std::env::current_dir()?;
+
+    Ok(current_dir
+        .ancestors()
+        .find(|dir| BuildSystem::detect(dir).is_some())
+        .unwrap_or(&current_dir)
+        .to_path_buf())
+}
+
+pub fn init_project(options: InitOptions) -> Result<ProjectInfo, ArkhamError> {
+    // The backend only lives in .arkham.conf : with --no-config it would go nowhere
+    if let (Some(backend), Some(false)) = (options.backup_backend, options.write_config) {
+        return Err(ArkhamError::InitError(format!(
+            "--backend {} is kept in {}, drop --no-config to set it",
+            backend.name(),
+            CONFIG_FILE
+        )));
+    }
+
+    // Project root : component's workspace, explicit or detected, always stored as an absolute path
+    let project_root = match (&options.context, &options.project_root) {
+        (Some(context), _) => context.root.clone(),
+        (None, Some(root)) => PathBuf::from(root),
+        (None, None) => detect_project_root()?,
+    };
+    let project_root = project_root.canonicalize().map_err(|e| {
+        ArkhamError::InitError(format!(
+            "Project root {} is not accessible: {}",
+            project_root.display(),
+            e
+        ))
+    })?;
+    if !project_root.is_dir() {
+        return Err(ArkhamError::InitError(format!(
+            "Project root {} is not a directory",
+            project_root.display()
+        )));
+    }
+    let root_str = project_root.to_string_lossy().to_string();
+
+    // Refuse to clobber existing versioning
+    let context = match options.context {
+        Some(ref context) => context.clone(),
+        None => ProjectContext::at(&project_root),
+    };
+    if context.info_file().exists() && !options.force {
+        return Err(ArkhamError::InitError(format!(
+            "{} already exists (use --force to overwrite)",
+            context.info_file().display()
+        )));
+    }
+
+    // Build System
+    let build_system = match options.build_system.or(BuildSystem::detect(&project_root)) {
+        Some(build_system) => build_system,
+        None => {
+            println!("No Makefile / CMakeLists.txt / Cargo.toml found, assuming make");
+            BuildSystem::Make
+        }
+    };
+    println!("Project root:  {}", root_str);
+    println!("Build system:  {}", build_system.name());
+
+    // Executable Name & Version
+    let project_name = match options.project_name {
+        Some(name) => {
+            validate_project_name(&name)?;
+            name
+        }
+        None => prompt_until_valid(
+            "Enter Executable name (Eg. CookieUFS): ",
+            validate_project_name,
+        )?,
+    };
+
+    let current_version = match options.version {
+        Some(version) => {
+            validate_version(&version)?;
+            version
+        }
+        None => prompt_until_valid("Enter the current version (Eg. 3.53): ", validate_version)?,
+    };
+
+    // Optional .arkham.conf
+    let write_conf = match options.write_config {
+        Some(choice) => choice,
+        None => {
+            let choice = get_user_input(&format!(
+                "Write project config {}? (yes [y] | no [n]) = ",
+                CONFIG_FILE
+            ))?;
+            choice.eq_ignore_ascii_case("yes") || choice.eq_ignore_ascii_case("y")
+        }
+    };
+
+    if write_conf {
+        if project_root.join(CONFIG_FILE).exists() && !options.force {
+            println!(
+                "{} already exists, keeping it (use --force to overwrite){}",
+                CONFIG_FILE,
+                match options.backup_backend {
+                    Some(_) => ", --backend not applied",
+                    None => "",
+                }
+            );
+        } else {
+            // --force rewrites the settings, hooks, size limits & backend already there stay (unless --backend)
+            let existing = read_config(&project_root).unwrap_or_default();
+            let config = ArkhamConfig {
+                build_system: Some(build_system),
+                backup_backend: options.backup_backend.unwrap_or(existing.backup_backend),
+                ..existing
+            };
+            write_config(&project_root, &config)?;
+            println!("Wrote {}", CONFIG_FILE);
+        }
+    }
+
+    let mut info = ProjectInfo::new(project_name, current_version, root_str);
+    info.artifacts = vec![build_system.default_artifact(&info.project_name)];
+    for artifact in options.extra_artifacts {
+        validate_artifact_path(&artifact)?;
+        if !info.artifacts.contains(&artifact) {
+            info.artifacts.push(artifact);
+        }
+    }
+    println!("Artifacts:     {}", info.artifacts.join(", "));
+
+    write_version_info(&context, &info)?;
+
+    display_success_msg(&format!(
+        "Arkham initialized!\nProject Name: {}\nCurrent Version: {}",
+        info.project_name, info.current_version
+    ));
+
+    Ok(info)
+}
@@ -0,0 +1,148 @@
+/*
+ PROJECT LOCK : one arkham at a time may change the project state
+*/
+use crate::arkham_constants::{LOCK_FILE, LOCK_HELD_ENV};
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_project::ProjectContext;
+
+use chrono::Local;
+use fs2::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+// Held until dropped : the OS releases the flock even if we get killed
+pub struct ProjectLock {
+    file: Option<File>, // None => borrowed from the arkham that ran our hook
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        if let Some(file) = &self.file {
+            let _ = file.set_len(0);
+            let _ = file.unlock();
+        }
+    }
+}
+
+// Who holds it, as written by the holder : "pid 1234 (alice, build) since 2024-05-01 10:12:00"
+fn lock_holder(context: &ProjectContext) -> String {
+    match fs::read_to_string(context.path(LOCK_FILE)) {
+        Ok(holder) if !holder.trim().is_empty() => holder.trim().to_string(),
+        _ => "another arkham process".to_string(),
+    }
+}
+
+/*
+    Exclusive lock on <root>/.arkham.lock (a workspace shares one lock : components share the build tree)
+    no_wait => fail straight away instead of blocking
+*/
+pub fn acquire_lock(
+    context: &ProjectContext,
+    command: &str,
+    no_wait: bool,
+) -> Result<ProjectLock, ArkhamError> {
+    let lock_path = context.path(LOCK_FILE);
+
+    // Started from a hook : the parent holds this very lock, waiting on it would deadlock
+    if std::env::var_os(LOCK_HELD_ENV).is_some_and(|held| Path::new(&held) == lock_path) {
+        return Ok(ProjectLock { file: None });
+    }
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path)?;
+
+    if file.try_lock_exclusive().is_err() {
+        let holder = lock_holder(context);
+        if no_wait {
+            return Err(ArkhamError::LockError(format!(
+                "held by {} ({})",
+                holder,
+                lock_path.display()
+            )));
+        }
+
+        println!("Waiting for {} to finish..", holder);
+        file.lock_exclusive()?;
+    }
+
+    // Leave a note for whoever comes next
+    file.set_len(0)?;
+    writeln!(
+        file,
+        "pid {} ({}, {}) since {}",
+        std::process::id(),
+        std::env::var("USER").unwrap_or("unknown".to_string()),
+        command,
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    )?;
+    file.flush()?;
+
+    Ok(ProjectLock { file: Some(file) })
+}
+
+// Another arkham (or our parent, for a hook) is changing the project state right now
+pub fn lock_is_held(context: &ProjectContext) -> bool {
+    let lock_path = context.path(LOCK_FILE);
+    if std::env::var_os(LOCK_HELD_ENV).is_some_and(|held| Path::new(&held) == lock_path) {
+        return true;
+    }
+
+    match File::open(&lock_path) {
+        Ok(file) => match file.try_lock_shared() {
+            Ok(()) => {
+                let _ = file.unlock();
+                false
+            }
+            Err(_) => true,
+        },
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn no_wait_fails_naming_the_holder() {
+        let root = tempfile::tempdir().unwrap();
+        let context = ProjectContext::at(root.path());
+
+        let held = acquire_lock(&context, "build", true).unwrap();
+        assert!(lock_is_held(&context));
+        match acquire_lock(&context, "backup", true) {
+            Err(ArkhamError::LockError(message)) => assert!(message.contains("build")),
+            other => panic!("expected a LockError, got {:?}", other.err()),
+        }
+
+        drop(held);
+        assert!(!lock_is_held(&context));
+        assert!(acquire_lock(&context, "backup", true).is_ok());
+    }
+
+    #[test]
+    fn waits_until_the_holder_is_done() {
+        let root = tempfile::tempdir().unwrap();
+        let context = ProjectContext::at(root.path());
+
+        let held = acquire_lock(&context, "build", true).unwrap();
+        let holder = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            drop(held);
+        });
+
+        let started = Instant::now();
+        let _lock = acquire_lock(&context, "backup", false).unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(250));
+        holder.join().unwrap();
+
+        let note = fs::read_to_string(context.path(LOCK_FILE)).unwrap();
+        assert!(note.contains("backup"), "{}", note);
+    }
+}
@@ -0,0 +1,215 @@
+/*
+ PROJECT DISCOVERY : where the arkham state files live
+*/
+use crate::arkham_constants::{
+    PREV_BUILDS_DIR, VERSION_INFO_FILE, VERSION_LOGS_FILE, WORKSPACE_FILE,
+};
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_workspace::{read_workspace, Component};
+
+use std::path::{Path, PathBuf};
+
+// Every path arkham touches is resolved against the project root
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ProjectContext {
+    pub root: PathBuf,
+    pub in_workspace: bool,
+    pub component: Option<Component>, // workspace component being worked on
+}
+
+impl ProjectContext {
+    pub fn at(root: &Path) -> Self {
+        ProjectContext {
+            root: root.to_path_buf(),
+            in_workspace: false,
+            component: None,
+        }
+    }
+
+    pub fn with_component(&self, component: Component) -> Self {
+        ProjectContext {
+            root: self.root.clone(),
+            in_workspace: true,
+            component: Some(component),
+        }
+    }
+
+    // Walk up from the cwd to the nearest .version.info / .arkham.workspace (like git looks for .git)
+    pub fn discover(component_name: Option<&str>) -> Result<Option<Self>, ArkhamError> {
+        Self::discover_from(&std::env::current_dir()?, component_name)
+    }
+
+    fn discover_from(
+        start: &Path,
+        component_name: Option<&str>,
+    ) -> Result<Option<Self>, ArkhamError> {
+        for dir in start.ancestors() {
+            if dir.join(VERSION_INFO_FILE).is_file() {
+                if let Some(name) = component_name {
+                    return Err(ArkhamError::WorkspaceError(format!(
+                        "-p {}: {} is a single project, not a workspace",
+                        name,
+                        dir.display()
+                    )));
+                }
+                return Ok(Some(ProjectContext::at(dir)));
+            }
+
+            if dir.join(WORKSPACE_FILE).is_file() {
+                return Self::select_component(dir, component_name).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    // -p <component>, or the only component there is
+    fn select_component(root: &Path, component_name: Option<&str>) -> Result<Self, ArkhamError> {
+        let components = read_workspace(root)?;
+        let mut workspace = ProjectContext::at(root);
+        workspace.in_workspace = true;
+
+        let selected = match component_name {
+            Some(name) => components.iter().find(|c| c.name == name).cloned(),
+            None if components.len() == 1 => components.first().cloned(),
+            None => return Ok(workspace),
+        };
+
+        match selected {
+            Some(component) => Ok(workspace.with_component(component)),
+            None => Err(ArkhamError::WorkspaceError(format!(
+                "Unknown component '{}' (available: {})",
+                component_name.unwrap_or_default(),
+                components
+                    .iter()
+                    .map(|c| c.name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ))),
+        }
+    }
+
+    // Discovered project, else the cwd (nothing set up yet)
+    pub fn locate(component_name: Option<&str>) -> Result<Self, ArkhamError> {
+        match Self::discover(component_name)? {
+            Some(context) => Ok(context),
+            None => match component_name {
+                Some(name) => Err(ArkhamError::WorkspaceError(format!(
+                    "-p {}: no {} found in the current directory or above",
+                    name, WORKSPACE_FILE
+                ))),
+                None => Ok(ProjectContext::at(&std::env::current_dir()?)),
+            },
+        }
+    }
+
+    // Workspace root without -p : per-component commands can't guess which one
+    pub fn needs_component(&self) -> bool {
+        self.in_workspace && self.component.is_none()
+    }
+
+    pub fn path(&self, relative: &str) -> PathBuf {
+        self.root.join(relative)
+    }
+
+    pub fn info_file(&self) -> PathBuf {
+        match self.component {
+            Some(ref component) => self.path(&component.info_file),
+            None => self.path(VERSION_INFO_FILE),
+        }
+    }
+
+    pub fn log_file(&self) -> PathBuf {
+        match self.component {
+            Some(ref component) => self.path(&component.log_file),
+            None => self.path(VERSION_LOGS_FILE),
+        }
+    }
+
+    // Stats of every build that bumped a version : next to the log, .version.stats / .version.<component>.stats
+    pub fn stats_file(&self) -> PathBuf {
+        self.log_file().with_extension("stats")
+    }
+
+    // Components keep their build history apart : prev_builds/<component>/
+    pub fn prev_builds_dir(&self) -> PathBuf {
+        match self.component {
+            Some(ref component) => self.path(PREV_BUILDS_DIR).join(&component.name),
+            None => self.path(PREV_BUILDS_DIR),
+        }
+    }
+
+    pub fn build_target(&self) -> Option<&str> {
+        self.component
+            .as_ref()
+            .and_then(|component| component.build_target.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn nearest_project_above_the_cwd_wins() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("src/deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.path().join(VERSION_INFO_FILE), "").unwrap();
+
+        let context = ProjectContext::discover_from(&nested, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(context.root, root.path());
+        assert!(!context.in_workspace);
+
+        // a project inside the tree shadows the outer one
+        fs::write(root.path().join("src").join(VERSION_INFO_FILE), "").unwrap();
+        let context = ProjectContext::discover_from(&nested, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(context.root, root.path().join("src"));
+
+        assert!(matches!(
+            ProjectContext::discover_from(&nested, Some("kernel")),
+            Err(ArkhamError::WorkspaceError(_))
+        ));
+    }
+
+    #[test]
+    fn workspace_root_selects_the_component() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("kernel");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            root.path().join(WORKSPACE_FILE),
+            "[kernel]\ntarget=kernel\n\n[bootloader]\ntarget=boot\n",
+        )
+        .unwrap();
+
+        let context = ProjectContext::discover_from(&nested, Some("bootloader"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(context.root, root.path());
+        assert_eq!(
+            context.info_file(),
+            root.path().join(".version.bootloader.info")
+        );
+        assert_eq!(
+            context.stats_file(),
+            root.path().join(".version.bootloader.stats")
+        );
+
+        // two components, none picked : the workspace itself
+        let context = ProjectContext::discover_from(&nested, None)
+            .unwrap()
+            .unwrap();
+        assert!(context.needs_component());
+
+        assert!(matches!(
+            ProjectContext::discover_from(&nested, Some("ramdisk")),
+            Err(ArkhamError::WorkspaceError(_))
+        ));
+    }
+}
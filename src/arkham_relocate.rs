@@ -0,0 +1,213 @@
+/*
+ STALE PROJECT ROOT : detection on startup & `arkham relocate`
+*/
+use crate::arkham_constants::{SNAPSHOT_DIR, VERSION_INFO_FILE};
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_utility::{
+    display_error_msg, display_header_msg, display_success_msg, get_user_input,
+};
+use crate::arkham_version::{read_version_info, write_version_info, ProjectInfo};
+
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+// Same directory? (a root that no longer exists never matches)
+fn same_root(stored_root: &str, actual_root: &Path) -> bool {
+    if stored_root.is_empty() {
+        return false;
+    }
+
+    match (
+        Path::new(stored_root).canonicalize(),
+        actual_root.canonicalize(),
+    ) {
+        (Ok(stored), Ok(actual)) => stored == actual,
+        _ => false,
+    }
+}
+
+// Warn when .version.info was copied/moved & offer to fix project_root
+// (only `prompt` asks : read-only commands & piped stdin just get a warning)
+pub fn check_project_root(context: &ProjectContext, prompt: bool) -> Result<(), ArkhamError> {
+    let info = match read_version_info(context)? {
+        Some(info) => info,
+        None => return Ok(()),
+    };
+
+    if same_root(&info.project_root, &context.root) {
+        return Ok(());
+    }
+
+    let actual_root = context.root.canonicalize()?;
+    if !prompt || !std::io::stdin().is_terminal() {
+        // stderr : stdout may be parsed (stats --json)
+        eprintln!(
+            "Warning: project root mismatch, stored {} but running in {}",
+            info.project_root,
+            actual_root.display()
+        );
+        eprintln!("Run 'arkham relocate' to fix it.");
+        return Ok(());
+    }
+
+    display_error_msg("Project root mismatch!");
+    println!("Stored project_root:  {}", info.project_root);
+    println!("Actual location:      {}", actual_root.display());
+
+    let choice =
+        get_user_input("Update project_root to the actual location? (yes [y] | no [n]) = ")?;
+    if choice.eq_ignore_ascii_case("yes") || choice.eq_ignore_ascii_case("y") {
+        let relocated_info = ProjectInfo {
+            project_root: actual_root.to_string_lossy().to_string(),
+            ..info.clone()
+        };
+        write_version_info(context, &relocated_info)?;
+        let fixed = fix_symlinks(&actual_root, Path::new(&info.project_root))?;
+        println!("project_root updated, {} symlink(s) fixed", fixed);
+    } else {
+        println!("Keeping stored project_root (run 'arkham relocate' to fix it later).");
+    }
+    Ok(())
+}
+
+// Symlinks anywhere in the project tree (.git & snapshots skipped)
+fn collect_symlinks(dir: &Path, links: &mut Vec<PathBuf>) -> Result<(), ArkhamError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let name = entry.file_name();
+
+        if file_type.is_symlink() {
+            links.push(entry.path());
+        } else if file_type.is_dir() && name != ".git" && name != SNAPSHOT_DIR {
+            collect_symlinks(&entry.path(), links)?;
+        }
+    }
+    Ok(())
+}
+
+// arkham relocate [--from <OLD_ROOT>] : rewrite project_root & retarget symlinks into the project
+pub fn relocate_project(
+    context: &ProjectContext,
+    old_root_override: Option<&str>,
+) -> Result<(), ArkhamError> {
+    let info = read_version_info(context)?.ok_or(ArkhamError::MissingVersionInfo)?;
+    let new_root = context.root.canonicalize()?;
+    let old_root = PathBuf::from(old_root_override.unwrap_or(&info.project_root));
+
+    display_header_msg(&format!("Relocating Project: {}", info.project_name));
+    println!("From: {}", old_root.display());
+    println!("To:   {}", new_root.display());
+
+    // project_root
+    if same_root(&info.project_root, &new_root) {
+        println!(
+            "{} already points to {}",
+            VERSION_INFO_FILE,
+            new_root.display()
+        );
+    } else {
+        let relocated_info = ProjectInfo {
+            project_root: new_root.to_string_lossy().to_string(),
+            ..info.clone()
+        };
+        write_version_info(context, &relocated_info)?;
+        println!("Updated project_root in {}", VERSION_INFO_FILE);
+    }
+
+    let fixed = fix_symlinks(&new_root, &old_root)?;
+
+    display_success_msg(&format!("Project relocated!\n{} symlink(s) fixed", fixed));
+    Ok(())
+}
+
+// Absolute symlink targets under the old root get moved under the new one
+fn fix_symlinks(new_root: &Path, old_root: &Path) -> Result<usize, ArkhamError> {
+    let mut links = Vec::new();
+    collect_symlinks(new_root, &mut links)?;
+
+    let mut fixed = 0;
+    for link in links {
+        let target = fs::read_link(&link)?;
+
+        if target.is_absolute() && !old_root.as_os_str().is_empty() {
+            if let Ok(relative) = target.strip_prefix(old_root) {
+                let new_target = new_root.join(relative);
+                fs::remove_file(&link)?;
+                std::os::unix::fs::symlink(&new_target, &link)?;
+                println!(
+                    "Fixed symlink {} -> {}",
+                    link.display(),
+                    new_target.display()
+                );
+                fixed += 1;
+                continue;
+            }
+        }
+
+        // relative links move with the project, only flag the broken ones
+        if !link.exists() {
+            println!(
+                "Warning: symlink {} -> {} is broken",
+                link.display(),
+                target.display()
+            );
+        }
+    }
+
+    Ok(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn moved_project_gets_its_root_and_links_back() {
+        let parent = tempfile::tempdir().unwrap();
+        let parent = parent.path().canonicalize().unwrap();
+        let (old_root, new_root) = (parent.join("old"), parent.join("new"));
+        fs::create_dir_all(old_root.join("lib")).unwrap();
+
+        let info = ProjectInfo::new(
+            "main".to_string(),
+            "1.00".to_string(),
+            old_root.to_string_lossy().to_string(),
+        );
+        write_version_info(&ProjectContext::at(&old_root), &info).unwrap();
+        fs::write(old_root.join("main_v_1.00"), "build").unwrap();
+        symlink(old_root.join("main_v_1.00"), old_root.join("main")).unwrap();
+        symlink("../main_v_1.00", old_root.join("lib/main")).unwrap();
+        fs::rename(&old_root, &new_root).unwrap();
+
+        let context = ProjectContext::at(&new_root);
+        assert!(!same_root(&info.project_root, &new_root));
+        relocate_project(&context, None).unwrap();
+
+        let relocated = read_version_info(&context).unwrap().unwrap();
+        assert!(same_root(&relocated.project_root, &new_root));
+        assert_eq!(
+            fs::read_link(new_root.join("main")).unwrap(),
+            new_root.join("main_v_1.00")
+        );
+        // relative links moved with the tree : left alone
+        assert_eq!(
+            fs::read_link(new_root.join("lib/main")).unwrap(),
+            Path::new("../main_v_1.00")
+        );
+    }
+
+    #[test]
+    fn a_root_that_is_gone_never_matches() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(same_root(&root.path().to_string_lossy(), root.path()));
+        assert!(!same_root("", root.path()));
+        assert!(!same_root(
+            &root.path().join("gone").to_string_lossy(),
+            root.path()
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+/*
+ RENDERING : terminal width, display width, wrapping & color for arkham's own output
+    boxes & colors only on a terminal with color allowed (no NO_COLOR / --no-color),
+    piped or redirected output gets plain ASCII lines instead
+    widths are display columns (unicode-width), never bytes : "é" is 1, "界" is 2
+*/
+use crate::arkham_utility::color_enabled;
+
+use std::io::IsTerminal;
+use tabled::settings::peaker::PriorityMax;
+use tabled::settings::width::Wrap;
+use tabled::settings::Width;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// Boxes never get wider than this, even on a wide terminal
+pub const MAX_BOX_WIDTH: usize = 80;
+// Narrowest box still worth drawing
+const MIN_BOX_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tone {
+    Plain,
+    Success,
+    Error,
+}
+
+impl Tone {
+    fn ansi_code(self) -> Option<&'static str> {
+        match self {
+            Tone::Plain => None,
+            Tone::Success => Some("32"), // green
+            Tone::Error => Some("31"),   // red
+        }
+    }
+}
+
+// Boxes & colors : stdout is a terminal & color is allowed
+pub fn fancy_output() -> bool {
+    std::io::stdout().is_terminal() && color_enabled()
+}
+
+// Columns of the terminal on stdout, else $COLUMNS, else None (piped : no width to fit)
+pub fn terminal_width() -> Option<usize> {
+    if let Some((terminal_size::Width(columns), _)) = terminal_size::terminal_size() {
+        return Some(columns as usize);
+    }
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|&columns| columns > 0)
+}
+
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+// text in tone's color when fancy_output, as is otherwise
+pub fn paint(text: &str, tone: Tone) -> String {
+    match tone.ansi_code() {
+        Some(code) if fancy_output() => format!("\x1b[{}m{}\x1b[0m", code, text),
+        _ => text.to_string(),
+    }
+}
+
+// Word wrap to width columns : words longer than a line get split, a blank line stays one
+pub fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in line.split_whitespace() {
+        let needed = match current.is_empty() {
+            true => display_width(word),
+            false => display_width(&current) + 1 + display_width(word),
+        };
+        if needed <= width {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+            continue;
+        }
+
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        // too long for any line : split it by columns
+        for c in word.chars() {
+            if display_width(&current) + c.width().unwrap_or(0) > width && !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+// ╔═══╗ box around message, lines wrapped & centered, width columns wide (borders included)
+pub fn boxed_lines(message: &str, width: usize) -> Vec<String> {
+    let width = width.max(MIN_BOX_WIDTH);
+    let content_width = width - 4; // borders & a space on each side
+
+    let mut lines = vec![format!("╔{}╗", "═".repeat(width - 2))];
+    for line in message.lines() {
+        for wrapped in wrap_line(line, content_width) {
+            let padding_total = content_width.saturating_sub(display_width(&wrapped));
+            let padding_left = padding_total / 2;
+            lines.push(format!(
+                "║{}{}{}║",
+                " ".repeat(padding_left + 1),
+                wrapped,
+                " ".repeat(padding_total - padding_left + 1)
+            ));
+        }
+    }
+    lines.push(format!("╚{}╝", "═".repeat(width - 2)));
+    lines
+}
+
+// Header for plain output : "== line ==", one per message line
+pub fn plain_header_lines(message: &str) -> Vec<String> {
+    message
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| format!("== {} ==", line.trim()))
+        .collect()
+}
+
+// A header the way this output can show it
+pub fn header_lines(message: &str, tone: Tone) -> Vec<String> {
+    let lines = match fancy_output() {
+        true => boxed_lines(
+            message,
+            terminal_width().unwrap_or(MAX_BOX_WIDTH).min(MAX_BOX_WIDTH),
+        ),
+        false => plain_header_lines(message),
+    };
+    lines.iter().map(|line| paint(line, tone)).collect()
+}
+
+// For tabled : shrink the widest columns until the table fits the terminal (piped : left as is)
+pub fn fit_to_terminal() -> Wrap<usize, PriorityMax> {
+    Width::wrap(terminal_width().unwrap_or(usize::MAX))
+        .keep_words(true)
+        .priority(PriorityMax)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_keeps_every_line_within_width() {
+        let line =
+            "Current version = 3.10 created (2 artifact(s), checksums in main_v_3.10.sha256)";
+        for width in [1, 5, 12, 30, 80] {
+            let lines = wrap_line(line, width);
+            assert!(lines.iter().all(|wrapped| display_width(wrapped) <= width));
+            assert_eq!(lines.join("").replace(' ', ""), line.replace(' ', ""));
+        }
+    }
+
+    #[test]
+    fn wrap_splits_long_words_and_keeps_blank_lines() {
+        assert_eq!(wrap_line("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(wrap_line("", 10), [""]);
+        // wide characters take two columns each
+        assert_eq!(wrap_line("日本語", 4), ["日本", "語"]);
+    }
+
+    #[test]
+    fn box_lines_are_exactly_width_wide() {
+        let message = "Project Name: CookieUFS\nCurrent Version: 3.53 with a rather long line that has to wrap";
+        for width in [MIN_BOX_WIDTH, 30, 60] {
+            let lines = boxed_lines(message, width);
+            assert!(lines.len() > 4);
+            assert!(lines.iter().all(|line| display_width(line) == width));
+        }
+        // narrower than the minimum : still MIN_BOX_WIDTH wide
+        let lines = boxed_lines(message, 3);
+        assert!(lines
+            .iter()
+            .all(|line| display_width(line) == MIN_BOX_WIDTH));
+    }
+}
@@ -0,0 +1,229 @@
+/*
+ LOG REPAIR : arkham repair rewrites a damaged .version.log
+*/
+use crate::arkham_constants::{LOG_FIELDS, VERSION_LOG_HEADER};
+use crate::arkham_errors::{validate_version, ArkhamError};
+use crate::arkham_fs::write_atomic;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_schema::{log_preamble, read_log_lines};
+use crate::arkham_utility::display_success_msg;
+use crate::arkham_version::{
+    format_log_row, parse_log_row, split_log_row, unescape_log_field, unquote_log_field,
+};
+
+use chrono::{Local, NaiveDateTime};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const QUARANTINE_SUFFIX: &str = ".quarantine";
+const BACKUP_SUFFIX: &str = ".bak";
+
+// What happened to one row
+enum RowFix {
+    Kept(String),
+    Requoted(String, usize), // canonical row, stray commas folded back into version_log
+    Quarantined(String),     // why
+}
+
+fn is_build_date(field: &str) -> bool {
+    NaiveDateTime::parse_from_str(field, "%Y-%m-%d %H:%M:%S").is_ok()
+}
+
+// <log><suffix> next to the log
+fn side_file(log_path: &Path, suffix: &str) -> PathBuf {
+    let file_name = log_path.file_name().unwrap_or_default().to_string_lossy();
+    log_path.with_file_name(format!("{}{}", file_name, suffix))
+}
+
+/*
+    Only version_log is free text : with more than LOG_FIELDS fields, the row is fixable when
+    field 0 is a version & the build date sits where it should counting from the end
+    (built_by, commit_id & the build stats never hold commas)
+*/
+fn repair_row(line: &str) -> RowFix {
+    let fields = parse_log_row(line);
+    let after_log = LOG_FIELDS - 2; // build_date .. errors
+
+    if validate_version(&fields[0]).is_err() {
+        return RowFix::Quarantined(format!("'{}' is not a version", fields[0]));
+    }
+
+    match fields.len() {
+        LOG_FIELDS => {
+            let canonical = format_log_row(&std::array::from_fn(|i| fields[i].as_str()));
+            if canonical == line {
+                RowFix::Kept(canonical)
+            } else {
+                RowFix::Requoted(canonical, 0)
+            }
+        }
+        n if n > LOG_FIELDS && is_build_date(&fields[n - after_log]) => {
+            // rejoin the raw pieces : "fixed a, b" keeps its spacing
+            let raw = split_log_row(line);
+            let version_log = unquote_log_field(&raw[1..n - after_log].join(","));
+            let version_log = unescape_log_field(version_log.trim());
+            let canonical = format_log_row(&std::array::from_fn(|i| match i {
+                0 => fields[0].as_str(),
+                1 => version_log.as_str(),
+                i => fields[n - LOG_FIELDS + i].as_str(),
+            }));
+            RowFix::Requoted(canonical, n - LOG_FIELDS)
+        }
+        n if n > LOG_FIELDS => RowFix::Quarantined(format!(
+            "{} fields and no build date where expected : can't tell which commas are stray",
+            n
+        )),
+        n => RowFix::Quarantined(format!("{} fields, expected {}", n, LOG_FIELDS)),
+    }
+}
+
+// arkham repair
+pub fn repair_version_log(context: &ProjectContext) -> Result<(), ArkhamError> {
+    let log_path = context.log_file();
+    if !log_path.exists() {
+        println!("No Version logs found!\n");
+        return Ok(());
+    }
+
+    let original = fs::read_to_string(&log_path)?;
+    let log = read_log_lines(&log_path)?;
+    let mut lines = log.rows;
+    let mut changes: Vec<String> = Vec::new();
+    let mut rows: Vec<String> = Vec::new();
+    let mut quarantined: Vec<String> = Vec::new();
+
+    // Header : the canonical one, whatever was there
+    match log.header {
+        Some((_, ref header)) if header == VERSION_LOG_HEADER => {}
+        Some((line_num, header)) if validate_version(&parse_log_row(&header)[0]).is_ok() => {
+            changes.push(format!("line {}: header was missing, added it", line_num));
+            lines.insert(0, (line_num, header));
+        }
+        Some((line_num, header)) => {
+            changes.push(format!("line {}: header '{}' replaced", line_num, header));
+        }
+        None => {}
+    }
+
+    for (line_num, line) in &lines {
+        let line_num = *line_num;
+        if line.trim().is_empty() {
+            changes.push(format!("line {}: empty line removed", line_num));
+            continue;
+        }
+
+        match repair_row(line) {
+            RowFix::Kept(row) => rows.push(row),
+            RowFix::Requoted(row, 0) => {
+                changes.push(format!("line {}: re-quoted as {}", line_num, row));
+                rows.push(row);
+            }
+            RowFix::Requoted(row, stray_commas) => {
+                changes.push(format!(
+                    "line {}: {} stray comma(s) folded back into version_log => {}",
+                    line_num, stray_commas, row
+                ));
+                rows.push(row);
+            }
+            RowFix::Quarantined(reason) => {
+                changes.push(format!(
+                    "line {}: quarantined ({}): {}",
+                    line_num, reason, line
+                ));
+                quarantined.push(format!("# line {}: {}\n{}\n", line_num, reason, line));
+            }
+        }
+    }
+
+    if changes.is_empty() {
+        display_success_msg("Version log is healthy, nothing to repair");
+        return Ok(());
+    }
+
+    // Original kept as <log>.bak, bad rows appended to <log>.quarantine
+    let backup_path = side_file(&log_path, BACKUP_SUFFIX);
+    write_atomic(&backup_path, original.as_bytes())?;
+
+    let quarantine_path = side_file(&log_path, QUARANTINE_SUFFIX);
+    if !quarantined.is_empty() {
+        let mut quarantine = fs::read_to_string(&quarantine_path).unwrap_or_default();
+        quarantine.push_str(&format!(
+            "# arkham repair {}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S")
+        ));
+        quarantine.push_str(&quarantined.concat());
+        write_atomic(&quarantine_path, quarantine.as_bytes())?;
+    }
+
+    let mut repaired = log_preamble();
+    for row in &rows {
+        repaired.push_str(row);
+        repaired.push('\n');
+    }
+    write_atomic(&log_path, repaired.as_bytes())?;
+
+    display_success_msg(&format!(
+        "Version log repaired: {} change(s)",
+        changes.len()
+    ));
+    for change in &changes {
+        println!("  - {}", change);
+    }
+    println!();
+    println!("Original log saved as:   {}", backup_path.display());
+    if !quarantined.is_empty() {
+        println!(
+            "Quarantined {} row(s) to: {}",
+            quarantined.len(),
+            quarantine_path.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROW: &str =
+        r#"3.10,"first build","2026-10-18 21:50:01","me","fc08a789","36","31962","0","0""#;
+
+    #[test]
+    fn canonical_row_is_kept() {
+        assert!(matches!(repair_row(ROW), RowFix::Kept(row) if row == ROW));
+    }
+
+    #[test]
+    fn unquoted_row_is_requoted() {
+        let row = "3.10,first build,2026-10-18 21:50:01,me,fc08a789,36,31962,0,0";
+        assert!(matches!(repair_row(row), RowFix::Requoted(fixed, 0) if fixed == ROW));
+    }
+
+    #[test]
+    fn stray_commas_fold_back_keeping_escaped_quotes() {
+        let row = r#"3.10,"said ""hi"", then",left,"2026-10-18 21:50:01","me","fc08a789","36","31962","0","0""#;
+        match repair_row(row) {
+            RowFix::Requoted(fixed, stray_commas) => {
+                assert_eq!(stray_commas, 1);
+                assert_eq!(parse_log_row(&fixed)[1], r#"said "hi", then,left"#);
+            }
+            _ => panic!("expected the stray comma to be folded back"),
+        }
+    }
+
+    #[test]
+    fn unfixable_rows_are_quarantined() {
+        assert!(matches!(
+            repair_row(r#"v3,"first build","2026-10-18 21:50:01""#),
+            RowFix::Quarantined(_)
+        ));
+        assert!(matches!(
+            repair_row(r#"3.10,"a","b","me","fc08a789","36","31962","0","0","extra""#),
+            RowFix::Quarantined(_)
+        ));
+        assert!(matches!(
+            repair_row(r#"3.10,"too few""#),
+            RowFix::Quarantined(_)
+        ));
+    }
+}
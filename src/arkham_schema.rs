@@ -0,0 +1,274 @@
+/*
+ STATE FILE SCHEMAS : which layout .version.info / .version.log are in, & upgrading old ones
+    schema 1 : original files, no marker
+    schema 2 : schema_version=<n> key in .version.info, #schema=<n> first line in .version.log
+    schema 3 : .version.log fields escaped (\n, \r, \\ & "") so a log message can span lines
+               last_build_* keys in .version.info : time, size & warning counts of the latest build
+    schema 4 : .version.log gets build_ms, artifact_bytes, warnings & errors columns
+*/
+use crate::arkham_constants::{
+    ARKHAM_VER, INFO_SCHEMA_VERSION, LOG_SCHEMA_MARKER, LOG_SCHEMA_VERSION, VERSION_LOG_HEADER,
+};
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_fs::write_atomic;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_trace::trace_read;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// .version.log split up : schema, header & data rows (1-based line numbers)
+pub struct LogLines {
+    pub schema: u32,
+    pub header: Option<(usize, String)>,
+    pub rows: Vec<(usize, String)>,
+}
+
+// Files from a newer arkham may carry fields we'd silently drop : don't touch them
+pub fn check_schema(file: &Path, schema: u32, supported: u32) -> Result<(), ArkhamError> {
+    if schema > supported {
+        return Err(ArkhamError::SchemaError(format!(
+            "{} was written by a newer arkham (schema {}, this arkham v{} reads up to {}). Upgrade arkham to use this project",
+            file.display(),
+            schema,
+            ARKHAM_VER,
+            supported
+        )));
+    }
+    Ok(())
+}
+
+pub fn parse_schema(file: &Path, value: &str) -> Result<u32, ArkhamError> {
+    value.trim().parse::<u32>().map_err(|_| {
+        ArkhamError::SchemaError(format!(
+            "{}: invalid schema version '{}'",
+            file.display(),
+            value
+        ))
+    })
+}
+
+/*
+    Read .version.log, any supported schema : header & rows come back in the current layout,
+    upgraded in memory (the file is only rewritten by migrate_state_files), schema is the one on disk
+*/
+pub fn read_log_lines(log_path: &Path) -> Result<LogLines, ArkhamError> {
+    trace_read(log_path);
+    let contents = fs::read_to_string(log_path)?;
+    let mut lines = contents.lines().enumerate().peekable();
+
+    let mut schema = 1;
+    if let Some((_, first)) = lines.peek() {
+        if let Some(value) = first.strip_prefix(LOG_SCHEMA_MARKER) {
+            schema = parse_schema(log_path, value)?;
+            check_schema(log_path, schema, LOG_SCHEMA_VERSION)?;
+            lines.next();
+        }
+    }
+
+    let mut numbered: Vec<(usize, String)> = lines
+        .map(|(line_num, line)| (line_num + 1, line.to_string()))
+        .collect();
+    if schema < LOG_SCHEMA_VERSION {
+        // every step keeps one line per line : the line numbers still point into the file
+        let mut upgraded: Vec<String> = numbered.iter().map(|(_, line)| line.clone()).collect();
+        for from in schema..LOG_SCHEMA_VERSION {
+            upgraded = upgrade_log(from, upgraded);
+        }
+        for ((_, line), upgraded_line) in numbered.iter_mut().zip(upgraded) {
+            *line = upgraded_line;
+        }
+    }
+
+    let mut numbered = numbered.into_iter();
+    let header = numbered.next();
+    let rows = numbered.collect();
+
+    Ok(LogLines {
+        schema,
+        header,
+        rows,
+    })
+}
+
+// Marker + header, what every new / rewritten log starts with
+pub fn log_preamble() -> String {
+    format!(
+        "{}{}\n{}\n",
+        LOG_SCHEMA_MARKER, LOG_SCHEMA_VERSION, VERSION_LOG_HEADER
+    )
+}
+
+// <file>.schema<n>.bak : the file as it was before migrating
+fn migration_backup(path: &Path, schema: u32) -> Result<PathBuf, ArkhamError> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let backup = path.with_file_name(format!("{}.schema{}.bak", file_name, schema));
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+// .version.info schema : schema_version key, 1 when missing
+fn info_schema(info_path: &Path, contents: &str) -> Result<u32, ArkhamError> {
+    for line in contents.lines() {
+        if let Some(value) = line.strip_prefix("schema_version=") {
+            return parse_schema(info_path, value);
+        }
+    }
+    Ok(1)
+}
+
+// One schema step for .version.log (header + rows, marker excluded)
+fn upgrade_log(from: u32, lines: Vec<String>) -> Vec<String> {
+    match from {
+        1 => lines, // 1 -> 2 : nothing but the #schema marker
+        // 2 -> 3 : a '\' used to be literal, now it starts an escape (rows never had '"' or newlines)
+        2 => lines
+            .into_iter()
+            .map(|line| line.replace('\\', "\\\\"))
+            .collect(),
+        // 3 -> 4 : new header, build stats unknown for the rows already there
+        3 => lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| match i {
+                0 => VERSION_LOG_HEADER.to_string(),
+                _ => format!("{},\"\",\"\",\"\",\"\"", line),
+            })
+            .collect(),
+        _ => lines,
+    }
+}
+
+// Read-only commands : old state files migrate_state_files would upgrade, nothing touched
+pub fn pending_migrations(context: &ProjectContext) -> Result<Vec<String>, ArkhamError> {
+    let mut pending: Vec<String> = Vec::new();
+
+    let info_path = context.info_file();
+    if info_path.is_file() {
+        trace_read(&info_path);
+        let schema = info_schema(&info_path, &fs::read_to_string(&info_path)?)?;
+        check_schema(&info_path, schema, INFO_SCHEMA_VERSION)?;
+        if schema < INFO_SCHEMA_VERSION {
+            pending.push(format!(
+                "{} is schema {}, not upgraded to {} yet",
+                info_path.display(),
+                schema,
+                INFO_SCHEMA_VERSION
+            ));
+        }
+    }
+
+    let log_path = context.log_file();
+    if log_path.is_file() {
+        let schema = read_log_lines(&log_path)?.schema;
+        if schema < LOG_SCHEMA_VERSION {
+            pending.push(format!(
+                "{} is schema {}, not upgraded to {} yet",
+                log_path.display(),
+                schema,
+                LOG_SCHEMA_VERSION
+            ));
+        }
+    }
+
+    Ok(pending)
+}
+
+/*
+    Startup of a writing command, under the project lock : bring old state files up to the current schema, one step at a time
+    (in place, the original kept as <file>.schema<n>.bak)
+    & refuse to go any further on files from a newer arkham
+*/
+pub fn migrate_state_files(context: &ProjectContext) -> Result<(), ArkhamError> {
+    let info_path = context.info_file();
+    if info_path.is_file() {
+        trace_read(&info_path);
+        let contents = fs::read_to_string(&info_path)?;
+        let schema = info_schema(&info_path, &contents)?;
+        check_schema(&info_path, schema, INFO_SCHEMA_VERSION)?;
+
+        if schema < INFO_SCHEMA_VERSION {
+            let backup = migration_backup(&info_path, schema)?;
+
+            // every info schema so far only added keys : the lines carry over, the schema key is new
+            let lines: Vec<&str> = contents
+                .lines()
+                .filter(|line| !line.starts_with("schema_version="))
+                .collect();
+
+            let migrated = format!(
+                "schema_version={}\n{}\n",
+                INFO_SCHEMA_VERSION,
+                lines.join("\n")
+            );
+            write_atomic(&info_path, migrated.as_bytes())?;
+            println!(
+                "Migrated {} from schema {} to {} (backup: {})",
+                info_path.display(),
+                schema,
+                INFO_SCHEMA_VERSION,
+                backup.display()
+            );
+        }
+    }
+
+    let log_path = context.log_file();
+    if log_path.is_file() {
+        let log = read_log_lines(&log_path)?;
+
+        if log.schema < LOG_SCHEMA_VERSION {
+            let backup = migration_backup(&log_path, log.schema)?;
+
+            // read_log_lines already brought the lines up to date
+            let lines = log.header.into_iter().chain(log.rows).map(|(_, line)| line);
+
+            let mut migrated = format!("{}{}\n", LOG_SCHEMA_MARKER, LOG_SCHEMA_VERSION);
+            for line in lines {
+                migrated.push_str(&line);
+                migrated.push('\n');
+            }
+            write_atomic(&log_path, migrated.as_bytes())?;
+            println!(
+                "Migrated {} from schema {} to {} (backup: {})",
+                log_path.display(),
+                log.schema,
+                LOG_SCHEMA_VERSION,
+                backup.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arkham_constants::LOG_FIELDS;
+    use crate::arkham_version::parse_log_row;
+
+    #[test]
+    fn log_upgrades_from_schema_2_to_current() {
+        let mut lines = vec![
+            "version_name,version_log,build_date,built_by,commit_id".to_string(),
+            r#"3.10,"moved C:\tmp\new","2026-10-18 21:50:01","me","fc08a789""#.to_string(),
+        ];
+        for from in 2..LOG_SCHEMA_VERSION {
+            lines = upgrade_log(from, lines);
+        }
+
+        assert_eq!(lines[0], VERSION_LOG_HEADER);
+        let fields = parse_log_row(&lines[1]);
+        assert_eq!(fields.len(), LOG_FIELDS);
+        // the backslashes stay literal, not \t & \n escapes
+        assert_eq!(fields[1], r"moved C:\tmp\new");
+        assert_eq!(fields[4], "fc08a789");
+        assert!(fields[5..].iter().all(|field| field.is_empty()));
+    }
+
+    #[test]
+    fn schema_1_log_only_gains_the_marker() {
+        let lines = vec!["a,b".to_string()];
+        assert_eq!(upgrade_log(1, lines.clone()), lines);
+    }
+}
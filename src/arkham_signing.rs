@@ -0,0 +1,267 @@
+/*
+ BUILD SIGNING : ed25519 signatures over stamped builds, for builds that must be provably ours
+    signing_key= in .arkham.conf (PKCS#8 PEM : openssl genpkey -algorithm ed25519 -out arkham.pem)
+    every build stamped by update_executable_version gets <build>.sig (hex) next to it,
+    rotated into prev_builds/ with the build
+    arkham verify <binary> : signature against a public key + the embedded version
+*/
+use crate::arkham_config::read_config;
+use crate::arkham_constants::{CONFIG_FILE, SIGNATURE_EXTENSION};
+use crate::arkham_errors::{validate_version, ArkhamError};
+use crate::arkham_project::ProjectContext;
+use crate::arkham_trace::trace_read;
+use crate::arkham_utility::display_success_msg;
+use crate::arkham_version::read_stamp;
+
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// <build>.sig, next to the build
+pub fn signature_path(build: &Path) -> PathBuf {
+    let mut file_name = build.file_name().unwrap_or_default().to_os_string();
+    file_name.push(SIGNATURE_EXTENSION);
+    build.with_file_name(file_name)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// Short, stable name for a public key : first 16 hex of its sha256
+pub fn key_id(key: &VerifyingKey) -> String {
+    to_hex(&Sha256::digest(key.as_bytes())[..8])
+}
+
+fn read_pem(path: &Path) -> Result<String, ArkhamError> {
+    trace_read(path);
+    fs::read_to_string(path)
+        .map_err(|e| ArkhamError::SigningError(format!("can't read key {}: {}", path.display(), e)))
+}
+
+pub fn load_signing_key(path: &Path) -> Result<SigningKey, ArkhamError> {
+    SigningKey::from_pkcs8_pem(&read_pem(path)?).map_err(|e| {
+        ArkhamError::SigningError(format!(
+            "{} is not an ed25519 private key (PKCS#8 PEM): {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+// Public key PEM, or a private key's public half
+pub fn load_verifying_key(path: &Path) -> Result<VerifyingKey, ArkhamError> {
+    let pem = read_pem(path)?;
+    VerifyingKey::from_public_key_pem(&pem)
+        .or_else(|_| SigningKey::from_pkcs8_pem(&pem).map(|key| key.verifying_key()))
+        .map_err(|e| {
+            ArkhamError::SigningError(format!(
+                "{} is not an ed25519 key (PEM): {}",
+                path.display(),
+                e
+            ))
+        })
+}
+
+// signing_key= from the config, loaded : None => builds go out unsigned
+pub fn configured_signing_key(context: &ProjectContext) -> Result<Option<SigningKey>, ArkhamError> {
+    match read_config(&context.root)?.signing_key {
+        Some(path) => Ok(Some(load_signing_key(&context.root.join(path))?)),
+        None => Ok(None),
+    }
+}
+
+// Detached signature of file, as written to <build>.sig
+pub fn sign_file(key: &SigningKey, file: &Path) -> Result<String, ArkhamError> {
+    let signature = key.sign(&fs::read(file)?);
+    Ok(format!("{}\n", to_hex(&signature.to_bytes())))
+}
+
+// main_v_3.07 => 3.07
+fn version_from_name(binary: &Path) -> Option<String> {
+    let file_name = binary.file_name()?.to_string_lossy().to_string();
+    let (_, version) = file_name.rsplit_once("_v_")?;
+    validate_version(version).ok().map(|_| version.to_string())
+}
+
+/*
+    arkham verify <binary> [--key PEM] [--signature SIG] [--expect-version X.YY]
+    key : --key, else verify_key=, else the public half of signing_key=
+    binary : a symlink is followed, the signature sits next to the build it points to
+*/
+pub fn verify_build(
+    context: &ProjectContext,
+    binary: &Path,
+    key: Option<&Path>,
+    signature: Option<&Path>,
+    expected_version: Option<&str>,
+) -> Result<(), ArkhamError> {
+    let config = read_config(&context.root)?;
+    let key_path = match (key, &config.verify_key, &config.signing_key) {
+        (Some(key), _, _) => key.to_path_buf(),
+        (None, Some(verify_key), _) => context.root.join(verify_key),
+        (None, None, Some(signing_key)) => context.root.join(signing_key),
+        (None, None, None) => {
+            return Err(ArkhamError::SigningError(format!(
+                "no key to verify with: pass --key <public key PEM> or set verify_key= in {}",
+                CONFIG_FILE
+            )))
+        }
+    };
+    let verifying_key = load_verifying_key(&key_path)?;
+
+    let build = fs::canonicalize(binary).map_err(|e| {
+        ArkhamError::SigningError(format!("can't open {}: {}", binary.display(), e))
+    })?;
+    let signature_file = signature
+        .map(Path::to_path_buf)
+        .unwrap_or(signature_path(&build));
+    trace_read(&signature_file);
+    let signature_hex = fs::read_to_string(&signature_file).map_err(|e| {
+        ArkhamError::SigningError(format!(
+            "no signature for {} ({}: {})",
+            build.display(),
+            signature_file.display(),
+            e
+        ))
+    })?;
+    let signature = from_hex(&signature_hex)
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or(ArkhamError::SigningError(format!(
+            "{} is not an ed25519 signature",
+            signature_file.display()
+        )))?;
+
+    // Signature first : a stamp is only worth reading from a build that is ours
+    trace_read(&build);
+    verifying_key
+        .verify(&fs::read(&build)?, &signature)
+        .map_err(|_| {
+            ArkhamError::SigningError(format!(
+                "BAD signature: {} was not signed by key {} or was changed after signing",
+                build.display(),
+                key_id(&verifying_key)
+            ))
+        })?;
+
+    let stamp = read_stamp(&build)?.ok_or(ArkhamError::SigningError(format!(
+        "{} is signed but carries no arkham version stamp",
+        build.display()
+    )))?;
+    let expected = match expected_version {
+        Some(version) => Some(validate_version(version).map(|_| version.to_string())?),
+        None => version_from_name(&build),
+    };
+    match expected {
+        Some(version) if version != stamp.version => {
+            return Err(ArkhamError::SigningError(format!(
+                "{} is signed but stamped {}, expected {}",
+                build.display(),
+                stamp.version,
+                version
+            )))
+        }
+        Some(_) => {}
+        None => {
+            return Err(ArkhamError::SigningError(format!(
+            "signature OK, but no version to check the stamp ({}) against: pass --expect-version",
+            stamp.version
+        )))
+        }
+    }
+
+    println!("Build:      {}", build.display());
+    println!(
+        "Signature:  {} (key {})",
+        signature_file.display(),
+        key_id(&verifying_key)
+    );
+    println!("Stamp:      {} built {}", stamp.version, stamp.build_date);
+    display_success_msg(&format!(
+        "Verified: {} is signed & stamped {}",
+        build.file_name().unwrap_or_default().to_string_lossy(),
+        stamp.version
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+    use ed25519_dalek::pkcs8::EncodePrivateKey;
+
+    const BUILD: &[u8] =
+        b"\x7fELF...\n--VERSION_INFO_START--\nVersion: 1.00\nBuild Date: 2026-10-18\n--VERSION_INFO_END--";
+
+    // main_v_1.00 signed with key 1, key1.pem & key2.pem next to it
+    fn signed_build() -> (tempfile::TempDir, ProjectContext, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        for seed in [1, 2] {
+            let pem = SigningKey::from_bytes(&[seed; 32])
+                .to_pkcs8_pem(LineEnding::LF)
+                .unwrap();
+            fs::write(dir.path().join(format!("key{}.pem", seed)), pem.as_bytes()).unwrap();
+        }
+        let build = dir.path().join("main_v_1.00");
+        fs::write(&build, BUILD).unwrap();
+        let key = load_signing_key(&dir.path().join("key1.pem")).unwrap();
+        fs::write(signature_path(&build), sign_file(&key, &build).unwrap()).unwrap();
+        let context = ProjectContext::at(dir.path());
+        (dir, context, build)
+    }
+
+    fn verify(context: &ProjectContext, build: &Path, key: &str) -> Result<(), ArkhamError> {
+        verify_build(context, build, Some(&context.root.join(key)), None, None)
+    }
+
+    #[test]
+    fn right_key_verifies() {
+        let (_dir, context, build) = signed_build();
+        assert!(verify(&context, &build, "key1.pem").is_ok());
+    }
+
+    #[test]
+    fn wrong_key_is_a_bad_signature() {
+        let (_dir, context, build) = signed_build();
+        match verify(&context, &build, "key2.pem") {
+            Err(ArkhamError::SigningError(msg)) => assert!(msg.starts_with("BAD signature")),
+            other => panic!("expected a bad signature, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn changed_build_is_a_bad_signature() {
+        let (_dir, context, build) = signed_build();
+        let mut changed = BUILD.to_vec();
+        changed[1] ^= 1;
+        fs::write(&build, changed).unwrap();
+        assert!(matches!(
+            verify(&context, &build, "key1.pem"),
+            Err(ArkhamError::SigningError(msg)) if msg.starts_with("BAD signature")
+        ));
+    }
+
+    #[test]
+    fn stamp_must_match_the_expected_version() {
+        let (_dir, context, build) = signed_build();
+        let key = context.root.join("key1.pem");
+        assert!(matches!(
+            verify_build(&context, &build, Some(&key), None, Some("1.01")),
+            Err(ArkhamError::SigningError(msg)) if msg.contains("stamped 1.00, expected 1.01")
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+/*
+ SIZE GATE : size_budget & size_growth_limit in .arkham.conf, checked before a build gets stamped
+    sizes are of each stamped artifact (artifacts= in .version.info), compared with the build of the
+    current version (next to the artifact, else the newest one in prev_builds/)
+    both limits apply to every artifact on its own
+    size_gate=fail (default) => no version bump, size_gate=warn => reported only
+*/
+use crate::arkham_config::{ArkhamConfig, SizeGateAction, SizeGrowth};
+use crate::arkham_constants::{BUILD_SIDECAR_EXTENSIONS, VERSION_INFO_FILE};
+use crate::arkham_dryrun::DryRun;
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_stats::{format_bytes, format_size_delta};
+use crate::arkham_version::{
+    artifact_file_name, kept_build, stamp_date, stamped_size, ProjectInfo,
+};
+
+use std::fs;
+use std::path::PathBuf;
+
+// One new build against the previous one
+pub struct ArtifactSize {
+    pub artifact: String,
+    pub bytes: u64,
+    pub previous: Option<(String, u64)>, // version & size of the build it replaces
+}
+
+impl ArtifactSize {
+    pub fn delta(&self) -> Option<i64> {
+        self.previous
+            .as_ref()
+            .map(|(_, previous)| self.bytes as i64 - *previous as i64)
+    }
+
+    // "main: 31.2 KiB (+15.7 KiB vs 3.01)"
+    pub fn summary(&self) -> String {
+        match (&self.previous, self.delta()) {
+            (Some((version, _)), Some(delta)) => format!(
+                "{}: {} ({} vs {})",
+                self.artifact,
+                format_bytes(self.bytes),
+                format_size_delta(delta),
+                version
+            ),
+            _ => format!(
+                "{}: {} (no previous build)",
+                self.artifact,
+                format_bytes(self.bytes)
+            ),
+        }
+    }
+}
+
+// Every artifact of the new build & the configured limits
+pub struct SizeCheck {
+    pub artifacts: Vec<ArtifactSize>,
+    pub exceeded: Vec<String>, // limits gone over, as messages
+}
+
+impl SizeCheck {
+    // One summary line per artifact
+    pub fn summary(&self) -> String {
+        self.artifacts
+            .iter()
+            .map(ArtifactSize::summary)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+// 5% / 4.0 KiB
+fn growth_label(growth: SizeGrowth) -> String {
+    match growth {
+        SizeGrowth::Bytes(bytes) => format_bytes(bytes),
+        SizeGrowth::Percent(_) => growth.name(),
+    }
+}
+
+// Build of the current version : next to the artifact, moved to prev_builds/, else the newest there
+fn previous_build(
+    context: &ProjectContext,
+    artifact: &str,
+    current_version: &str,
+) -> Option<(String, PathBuf)> {
+    if let Some(path) = kept_build(context, artifact, current_version) {
+        return Some((current_version.to_string(), path));
+    }
+
+    let prefix = format!("{}_v_", artifact_file_name(artifact));
+    let mut builds: Vec<(String, PathBuf, fs::Metadata)> = Vec::new();
+    for entry in fs::read_dir(context.prev_builds_dir()).ok()?.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let is_sidecar = BUILD_SIDECAR_EXTENSIONS
+            .iter()
+            .any(|ext| file_name.ends_with(ext));
+        if let (Some(version), false) = (file_name.strip_prefix(&prefix), is_sidecar) {
+            if let Ok(meta) = entry.metadata() {
+                builds.push((version.to_string(), entry.path(), meta));
+            }
+        }
+    }
+    builds
+        .into_iter()
+        .max_by_key(|(_, _, meta)| meta.modified().ok())
+        .map(|(version, path, _)| (version, path))
+}
+
+// A build that left an artifact out is a build problem, not a size one
+pub fn ensure_artifacts_built(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+) -> Result<(), ArkhamError> {
+    let missing: Vec<&str> = info
+        .artifacts
+        .iter()
+        .filter(|artifact| !context.path(artifact).is_file())
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(ArkhamError::BuildError(format!(
+            "the build did not produce {} (artifacts= in {})",
+            missing.join(", "),
+            VERSION_INFO_FILE
+        )));
+    }
+    Ok(())
+}
+
+// Size of every artifact just built once stamped as version, each checked against the config
+pub fn check_size(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    version: &str,
+    config: &ArkhamConfig,
+) -> Result<SizeCheck, ArkhamError> {
+    ensure_artifacts_built(context, info)?;
+
+    let mut check = SizeCheck {
+        artifacts: Vec::new(),
+        exceeded: Vec::new(),
+    };
+    for artifact in &info.artifacts {
+        let bytes = stamped_size(&context.path(artifact), version, &stamp_date())?;
+        let previous = previous_build(context, artifact, &info.current_version)
+            .and_then(|(version, path)| fs::metadata(path).ok().map(|meta| (version, meta.len())));
+
+        if let Some(budget) = config.size_budget {
+            if bytes > budget {
+                check.exceeded.push(format!(
+                    "{} is {}, {} over the size_budget of {}",
+                    artifact,
+                    format_bytes(bytes),
+                    format_bytes(bytes - budget),
+                    format_bytes(budget)
+                ));
+            }
+        }
+        if let (Some(growth), Some((previous_version, previous_bytes))) =
+            (config.size_growth_limit, &previous)
+        {
+            let limit = growth.limit(*previous_bytes);
+            if bytes > previous_bytes + limit {
+                check.exceeded.push(format!(
+                    "{} grew by {} since {}, size_growth_limit is {}",
+                    artifact,
+                    format_size_delta(bytes as i64 - *previous_bytes as i64),
+                    previous_version,
+                    growth_label(growth)
+                ));
+            }
+        }
+
+        check.artifacts.push(ArtifactSize {
+            artifact: artifact.clone(),
+            bytes,
+            previous,
+        });
+    }
+    Ok(check)
+}
+
+// Reports every limit gone over : size_gate=fail => Err, the version is left alone
+pub fn enforce_size_gate(check: &SizeCheck, config: &ArkhamConfig) -> Result<(), ArkhamError> {
+    if check.exceeded.is_empty() {
+        return Ok(());
+    }
+    for problem in &check.exceeded {
+        println!("Size warning: {}", problem);
+    }
+    match config.size_gate {
+        SizeGateAction::Warn => Ok(()),
+        SizeGateAction::Fail => Err(ArkhamError::SizeGateError(format!(
+            "{} size limit(s) exceeded, built but the version was left alone (size_gate=warn to bump anyway)",
+            check.exceeded.len()
+        ))),
+    }
+}
+
+// --dry-run : which limits a build would be checked against
+pub fn plan_size_gate(config: &ArkhamConfig, plan: &mut DryRun) {
+    let mut limits = Vec::new();
+    if let Some(budget) = config.size_budget {
+        limits.push(format!("size_budget {}", format_bytes(budget)));
+    }
+    if let Some(growth) = config.size_growth_limit {
+        limits.push(format!("size_growth_limit {}", growth_label(growth)));
+    }
+    if !limits.is_empty() {
+        plan.note(&format!(
+            "size gate ({}): {}",
+            config.size_gate.name(),
+            limits.join(", ")
+        ));
+    }
+}
@@ -0,0 +1,383 @@
+/*
+ GIT-FREE SNAPSHOT BACKEND : compressed tarball of the project tree per version
+*/
+use crate::arkham_constants::{
+    ARKHAM_PROTECTED_FILES, IGNORE_FILE, SNAPSHOT_DIR, SNAPSHOT_ID_PREFIX,
+};
+use crate::arkham_errors::ArkhamError;
+
+use chrono::Local;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use tar::{Archive, Builder};
+
+// Protected files + .gitignore + .arkhamignore patterns
+pub struct IgnoreList {
+    patterns: Vec<String>,
+}
+
+impl IgnoreList {
+    pub fn load(project_root: &Path, artifacts: &[String]) -> Result<Self, ArkhamError> {
+        // Protected files only ever live at the project root
+        let mut patterns: Vec<String> = ARKHAM_PROTECTED_FILES
+            .iter()
+            .map(|file| format!("/{}", file))
+            .collect();
+
+        // Every artifact symlink & its stamped copies are build outputs, not sources
+        for artifact in artifacts.iter().filter(|artifact| !artifact.is_empty()) {
+            patterns.push(format!("/{}", artifact));
+            patterns.push(format!("/{}_v_*", artifact));
+        }
+
+        for ignore_file in [".gitignore", IGNORE_FILE] {
+            let ignore_path = project_root.join(ignore_file);
+            if !ignore_path.exists() {
+                continue;
+            }
+
+            for line in fs::read_to_string(&ignore_path)?.lines() {
+                let line = line.trim();
+                // negations ('!pattern') are not supported
+                if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+                    continue;
+                }
+                patterns.push(line.to_string());
+            }
+        }
+
+        Ok(IgnoreList { patterns })
+    }
+
+    // rel_path : path relative to the project root
+    pub fn is_ignored(&self, rel_path: &Path) -> bool {
+        let rel = rel_path.to_string_lossy();
+        let components: Vec<&str> = rel.split('/').filter(|c| !c.is_empty()).collect();
+
+        self.patterns.iter().any(|pattern| {
+            let pattern = pattern.trim_end_matches('/');
+
+            if pattern.contains('/') {
+                // anchored to the root : matches the path itself or any of its parent dirs
+                let anchored = pattern.trim_start_matches('/');
+                (1..=components.len()).any(|n| glob_match(anchored, &components[..n].join("/")))
+            } else {
+                // bare name : matches at any depth
+                components.iter().any(|c| glob_match(pattern, c))
+            }
+        })
+    }
+
+    // Basename patterns for `diff -x`
+    pub fn diff_excludes(&self) -> Vec<String> {
+        self.patterns
+            .iter()
+            .filter_map(|pattern| pattern.trim_end_matches('/').rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string())
+            .collect()
+    }
+}
+
+// Minimal glob : '*' = any run of characters, '?' = any single character
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // let the last '*' swallow one more character
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+pub fn is_snapshot_id(state_id: &str) -> bool {
+    state_id.starts_with(SNAPSHOT_ID_PREFIX)
+}
+
+pub fn snapshot_path(project_root: &Path, snapshot_id: &str) -> PathBuf {
+    project_root
+        .join(SNAPSHOT_DIR)
+        .join(format!("{}.tar.gz", snapshot_id))
+}
+
+// Walk the project tree, skipping ignored entries
+fn collect_files(
+    project_root: &Path,
+    rel_dir: &Path,
+    ignore: &IgnoreList,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), ArkhamError> {
+    let mut entries: Vec<fs::DirEntry> =
+        fs::read_dir(project_root.join(rel_dir))?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name()); // stable archive order
+
+    for entry in entries {
+        let rel_path = rel_dir.join(entry.file_name());
+        if ignore.is_ignored(&rel_path) {
+            continue;
+        }
+
+        // symlinks are archived as links, never followed
+        if entry.file_type()?.is_dir() {
+            collect_files(project_root, &rel_path, ignore, files)?;
+        } else {
+            files.push(rel_path);
+        }
+    }
+    Ok(())
+}
+
+// snap-<version>-<timestamp>
+pub fn new_snapshot_id(version: &str) -> String {
+    format!(
+        "{}{}-{}",
+        SNAPSHOT_ID_PREFIX,
+        version,
+        Local::now().format("%Y%m%d%H%M%S")
+    )
+}
+
+// What a snapshot archives, relative to the root
+pub fn snapshot_files(root: &Path, artifacts: &[String]) -> Result<Vec<PathBuf>, ArkhamError> {
+    let ignore = IgnoreList::load(root, artifacts)?;
+    let mut files = Vec::new();
+    collect_files(root, Path::new(""), &ignore, &mut files)?;
+    Ok(files)
+}
+
+// Archive the project tree : returns the snapshot ID
+pub fn create_snapshot(
+    root: &Path,
+    artifacts: &[String],
+    version: &str,
+) -> Result<String, ArkhamError> {
+    let snapshot_id = new_snapshot_id(version);
+
+    fs::create_dir_all(root.join(SNAPSHOT_DIR))?;
+    let archive_path = snapshot_path(root, &snapshot_id);
+    if archive_path.exists() {
+        return Err(ArkhamError::BackupError(format!(
+            "Snapshot {} already exists",
+            snapshot_id
+        )));
+    }
+
+    let files = snapshot_files(root, artifacts)?;
+
+    let encoder = GzEncoder::new(File::create(&archive_path)?, Compression::default());
+    let mut builder = Builder::new(encoder);
+    builder.follow_symlinks(false);
+
+    for rel_path in &files {
+        builder.append_path_with_name(root.join(rel_path), rel_path)?;
+    }
+    builder.into_inner()?.finish()?;
+
+    println!(
+        "Snapshot {} created: {} files archived to {}/",
+        snapshot_id,
+        files.len(),
+        SNAPSHOT_DIR
+    );
+    Ok(snapshot_id)
+}
+
+/*
+    Extract a snapshot over the project tree : protected & ignored files are left untouched
+    Files added since (not in the snapshot, not ignored) are removed : the tree ends up as it was saved
+*/
+pub fn restore_snapshot(
+    root: &Path,
+    artifacts: &[String],
+    snapshot_id: &str,
+) -> Result<(), ArkhamError> {
+    let ignore = IgnoreList::load(root, artifacts)?;
+
+    let mut archive = open_snapshot(root, snapshot_id)?;
+    let mut archived: BTreeSet<PathBuf> = BTreeSet::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let rel_path = entry.path()?.into_owned();
+        if ignore.is_ignored(&rel_path) {
+            continue;
+        }
+
+        entry.unpack_in(root)?;
+        archived.insert(rel_path);
+    }
+
+    let mut current = Vec::new();
+    collect_files(root, Path::new(""), &ignore, &mut current)?;
+    let added: Vec<PathBuf> = current
+        .into_iter()
+        .filter(|rel_path| !archived.contains(rel_path))
+        .collect();
+    for rel_path in &added {
+        fs::remove_file(root.join(rel_path))?;
+        println!("Removed {} (added after the snapshot)", rel_path.display());
+        remove_empty_parents(root, rel_path);
+    }
+
+    println!(
+        "Restored {} files from snapshot {}, removed {}",
+        archived.len(),
+        snapshot_id,
+        added.len()
+    );
+    Ok(())
+}
+
+// Dirs emptied by a restore go too, up to the root (a dir with anything left in it stays)
+fn remove_empty_parents(root: &Path, rel_path: &Path) {
+    for dir in rel_path.ancestors().skip(1) {
+        if dir.as_os_str().is_empty() || fs::remove_dir(root.join(dir)).is_err() {
+            break;
+        }
+    }
+}
+
+// Files in a snapshot : path => (size, mtime), enough to tell what changed between two
+pub fn snapshot_listing(
+    project_root: &Path,
+    snapshot_id: &str,
+) -> Result<BTreeMap<PathBuf, (u64, u64)>, ArkhamError> {
+    let mut archive = open_snapshot(project_root, snapshot_id)?;
+    let mut listing = BTreeMap::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() {
+            let header = entry.header();
+            listing.insert(
+                entry.path()?.into_owned(),
+                (header.size()?, header.mtime()?),
+            );
+        }
+    }
+    Ok(listing)
+}
+
+// Extract a snapshot into an arbitrary directory (used by diff)
+pub fn extract_snapshot(
+    project_root: &Path,
+    snapshot_id: &str,
+    destination: &Path,
+) -> Result<(), ArkhamError> {
+    let mut archive = open_snapshot(project_root, snapshot_id)?;
+    archive.unpack(destination)?;
+    Ok(())
+}
+
+fn open_snapshot(
+    project_root: &Path,
+    snapshot_id: &str,
+) -> Result<Archive<GzDecoder<File>>, ArkhamError> {
+    let archive_path = snapshot_path(project_root, snapshot_id);
+    if !archive_path.exists() {
+        return Err(ArkhamError::BackupError(format!(
+            "Snapshot {} not found in {}/",
+            snapshot_id, SNAPSHOT_DIR
+        )));
+    }
+
+    Ok(Archive::new(GzDecoder::new(File::open(archive_path)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_star_and_question_mark() {
+        assert!(glob_match("*.o", "main.o"));
+        assert!(glob_match("*.o", ".o"));
+        assert!(!glob_match("*.o", "main.c"));
+        assert!(glob_match("main_v_*", "main_v_3.10"));
+        assert!(glob_match("?.c", "a.c"));
+        assert!(!glob_match("?.c", "ab.c"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("", "a"));
+    }
+
+    #[test]
+    fn glob_backtracks_past_an_early_match() {
+        assert!(glob_match("*ab", "aab"));
+        assert!(glob_match("*.tar.gz", "snap.tar.tar.gz"));
+        assert!(glob_match("naïve*", "naïve.txt"));
+    }
+
+    #[test]
+    fn anchored_and_bare_patterns() {
+        let ignore = IgnoreList {
+            patterns: vec!["/main".to_string(), "build/".to_string(), "*.o".to_string()],
+        };
+        assert!(ignore.is_ignored(Path::new("main")));
+        assert!(!ignore.is_ignored(Path::new("src/main")));
+        assert!(ignore.is_ignored(Path::new("build/out/app")));
+        assert!(ignore.is_ignored(Path::new("src/build/x")));
+        assert!(ignore.is_ignored(Path::new("src/util.o")));
+        assert!(!ignore.is_ignored(Path::new("src/util.c")));
+    }
+
+    #[test]
+    fn every_artifact_and_its_stamped_copies_are_ignored() {
+        let root = tempfile::tempdir().unwrap();
+        let artifacts = vec!["main".to_string(), "boot/boot.img".to_string()];
+        let ignore = IgnoreList::load(root.path(), &artifacts).unwrap();
+        assert!(ignore.is_ignored(Path::new("main")));
+        assert!(ignore.is_ignored(Path::new("main_v_3.10")));
+        assert!(ignore.is_ignored(Path::new("boot/boot.img")));
+        assert!(ignore.is_ignored(Path::new("boot/boot.img_v_1.02")));
+        assert!(!ignore.is_ignored(Path::new("boot/boot.c")));
+        assert!(!ignore.is_ignored(Path::new("src/main")));
+    }
+
+    #[test]
+    fn restore_removes_files_added_after_the_snapshot() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let artifacts = vec!["main".to_string()];
+        fs::write(root.join("main.c"), "int main() {}").unwrap();
+        fs::write(root.join("main"), "build").unwrap();
+        let snapshot_id = create_snapshot(root, &artifacts, "1.00").unwrap();
+
+        fs::write(root.join("main.c"), "changed").unwrap();
+        fs::create_dir_all(root.join("src/new")).unwrap();
+        fs::write(root.join("src/new/extra.c"), "added").unwrap();
+        fs::write(root.join("main_v_1.01"), "newer build").unwrap();
+        restore_snapshot(root, &artifacts, &snapshot_id).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.join("main.c")).unwrap(),
+            "int main() {}"
+        );
+        assert!(!root.join("src").exists());
+        // build outputs are ignored : never archived, never removed
+        assert!(root.join("main").exists());
+        assert!(root.join("main_v_1.01").exists());
+    }
+}
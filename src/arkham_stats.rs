@@ -0,0 +1,427 @@
+/*
+ BUILD STATS : arkham stats, build time, size & warnings across built versions
+    one row per version (its latest build), as a table or JSON
+    size = every artifact of the version, summed
+    every build lands in .version.stats as the bump is committed, backed up or not :
+    the log only fills in versions built before arkham kept that file
+    versions logged before arkham kept build stats are counted, not shown
+*/
+extern crate tabled;
+
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_render::fit_to_terminal;
+use crate::arkham_utility::display_header_msg;
+use crate::arkham_version::{
+    read_build_stats, read_version_info, read_version_log, BuildStats, BuiltVersion, LogEntry,
+};
+
+use tabled::{
+    settings::{object::Segment, Alignment, Modify, Padding, Style},
+    Table, Tabled,
+};
+
+#[derive(Tabled)]
+struct StatsRecord {
+    #[tabled(rename = "Version")]
+    version: String,
+    #[tabled(rename = "Built")]
+    date: String,
+    #[tabled(rename = "Build Time")]
+    build_time: String,
+    #[tabled(rename = "Size")]
+    size: String,
+    #[tabled(rename = "Size Change")]
+    delta: String,
+    #[tabled(rename = "Warnings")]
+    warnings: u64,
+    #[tabled(rename = "Errors")]
+    errors: u64,
+}
+
+// One built version & what its build cost
+struct VersionStats {
+    version: String,
+    build_date: String,
+    stats: BuildStats,
+    size_delta: Option<i64>, // vs the version before, None for the first one
+    logged: bool,            // in .version.log : backed up at least once
+}
+
+// 48213 => "47.1 KiB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", value, UNITS[unit]),
+    }
+}
+
+// +1.2 KiB / -340 B / 0 B
+pub fn format_size_delta(delta: i64) -> String {
+    let sign = match delta {
+        d if d > 0 => "+",
+        d if d < 0 => "-",
+        _ => "",
+    };
+    format!("{}{}", sign, format_bytes(delta.unsigned_abs()))
+}
+
+// 850ms / 12.4s
+pub fn format_duration(ms: u64) -> String {
+    match ms {
+        ms if ms < 1000 => format!("{}ms", ms),
+        ms => format!("{:.1}s", ms as f64 / 1000.0),
+    }
+}
+
+/*
+    Latest log entry per version, first logged first, then the versions only built since
+    .version.stats wins over the log : it has the latest build of the version, the log the one backed up
+    => (versions with stats, count of logged versions without)
+*/
+fn collect_stats(entries: Vec<LogEntry>, built: Vec<BuiltVersion>) -> (Vec<VersionStats>, usize) {
+    let mut latest: Vec<LogEntry> = Vec::new();
+    for entry in entries {
+        match latest.iter_mut().find(|seen| seen.version == entry.version) {
+            Some(seen) => *seen = entry,
+            None => latest.push(entry),
+        }
+    }
+
+    let mut rows: Vec<(String, String, Option<BuildStats>, bool)> = latest
+        .into_iter()
+        .map(|entry| (entry.version, entry.build_date, entry.stats, true))
+        .collect();
+    for build in built {
+        match rows.iter_mut().find(|row| row.0 == build.version) {
+            Some(row) => (row.1, row.2) = (build.build_date, Some(build.stats)),
+            None => rows.push((build.version, build.build_date, Some(build.stats), false)),
+        }
+    }
+
+    let without_stats = rows.iter().filter(|row| row.2.is_none()).count();
+    let mut versions: Vec<VersionStats> = Vec::new();
+    for (version, build_date, stats, logged) in rows {
+        if let Some(stats) = stats {
+            let size_delta = versions
+                .last()
+                .map(|prev| stats.artifact_bytes as i64 - prev.stats.artifact_bytes as i64);
+            versions.push(VersionStats {
+                version,
+                build_date,
+                stats,
+                size_delta,
+                logged,
+            });
+        }
+    }
+    (versions, without_stats)
+}
+
+// Slowest first, at most 3
+fn slowest(versions: &[VersionStats]) -> Vec<&VersionStats> {
+    let mut by_time: Vec<&VersionStats> = versions.iter().collect();
+    by_time.sort_by_key(|v| std::cmp::Reverse(v.stats.duration_ms));
+    by_time.truncate(3);
+    by_time
+}
+
+// first -> last size change & the average per release (None with fewer than 2 versions)
+fn size_growth(versions: &[VersionStats]) -> Option<(i64, i64)> {
+    let (first, last) = (versions.first()?, versions.last()?);
+    if versions.len() < 2 {
+        return None;
+    }
+    let growth = last.stats.artifact_bytes as i64 - first.stats.artifact_bytes as i64;
+    Some((growth, growth / (versions.len() as i64 - 1)))
+}
+
+fn print_table(project_name: &str, versions: &[VersionStats], without_stats: usize) {
+    display_header_msg(&format!(
+        "Build Stats: {} ({} version(s))",
+        project_name,
+        versions.len()
+    ));
+
+    let records: Vec<StatsRecord> = versions
+        .iter()
+        .map(|v| StatsRecord {
+            version: v.version.clone(),
+            date: v.build_date.clone(),
+            build_time: format_duration(v.stats.duration_ms),
+            size: format_bytes(v.stats.artifact_bytes),
+            delta: v
+                .size_delta
+                .map(format_size_delta)
+                .unwrap_or("-".to_string()),
+            warnings: v.stats.warnings,
+            errors: v.stats.errors,
+        })
+        .collect();
+
+    let mut table = Table::new(records);
+    let styled_table = table
+        .with(Style::ascii())
+        .with(Padding::new(1, 1, 0, 0))
+        .with(Modify::new(Segment::all()).with(Alignment::center()))
+        .with(fit_to_terminal());
+    println!("{}\n", styled_table);
+
+    if let (Some((growth, per_release)), Some(first), Some(last)) =
+        (size_growth(versions), versions.first(), versions.last())
+    {
+        println!(
+            "Size:           {} ({}) -> {} ({}), {} over {} release(s), {} per release",
+            format_bytes(first.stats.artifact_bytes),
+            first.version,
+            format_bytes(last.stats.artifact_bytes),
+            last.version,
+            format_size_delta(growth),
+            versions.len() - 1,
+            format_size_delta(per_release)
+        );
+        if let Some(jump) = versions
+            .iter()
+            .max_by_key(|v| v.size_delta.unwrap_or(i64::MIN))
+        {
+            println!(
+                "Biggest jump:   {} ({})",
+                jump.version,
+                format_size_delta(jump.size_delta.unwrap_or(0))
+            );
+        }
+        println!(
+            "Warnings:       {} in {} -> {} in {}",
+            first.stats.warnings, first.version, last.stats.warnings, last.version
+        );
+    }
+
+    let slowest: Vec<String> = slowest(versions)
+        .iter()
+        .map(|v| format!("{} ({})", v.version, format_duration(v.stats.duration_ms)))
+        .collect();
+    println!("Slowest builds: {}", slowest.join(", "));
+
+    let unlogged: Vec<&str> = versions
+        .iter()
+        .filter(|v| !v.logged)
+        .map(|v| v.version.as_str())
+        .collect();
+    if !unlogged.is_empty() {
+        println!(
+            "Not backed up:  {} ('arkham backup' logs the current one)",
+            unlogged.join(", ")
+        );
+    }
+    if without_stats > 0 {
+        println!(
+            "{} logged version(s) have no build stats (logged before arkham recorded them)",
+            without_stats
+        );
+    }
+    println!();
+}
+
+// "..." with JSON escapes
+fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn print_json(project_name: &str, versions: &[VersionStats], without_stats: usize) {
+    let rows: Vec<String> = versions
+        .iter()
+        .map(|v| {
+            format!(
+                "    {{\"version\": {}, \"build_date\": {}, \"build_ms\": {}, \"artifact_bytes\": {}, \"size_delta\": {}, \"warnings\": {}, \"errors\": {}, \"logged\": {}}}",
+                json_string(&v.version),
+                json_string(&v.build_date),
+                v.stats.duration_ms,
+                v.stats.artifact_bytes,
+                v.size_delta.map_or("null".to_string(), |d| d.to_string()),
+                v.stats.warnings,
+                v.stats.errors,
+                v.logged
+            )
+        })
+        .collect();
+    let slowest: Vec<String> = slowest(versions)
+        .iter()
+        .map(|v| {
+            format!(
+                "{{\"version\": {}, \"build_ms\": {}}}",
+                json_string(&v.version),
+                v.stats.duration_ms
+            )
+        })
+        .collect();
+    let (growth, per_release) = match size_growth(versions) {
+        Some((growth, per_release)) => (growth.to_string(), per_release.to_string()),
+        None => ("null".to_string(), "null".to_string()),
+    };
+
+    println!("{{");
+    println!("  \"project\": {},", json_string(project_name));
+    if rows.is_empty() {
+        println!("  \"versions\": [],");
+    } else {
+        println!("  \"versions\": [\n{}\n  ],", rows.join(",\n"));
+    }
+    println!("  \"size_growth_bytes\": {},", growth);
+    println!("  \"size_growth_per_release_bytes\": {},", per_release);
+    println!("  \"slowest_builds\": [{}],", slowest.join(", "));
+    println!("  \"versions_without_stats\": {}", without_stats);
+    println!("}}");
+}
+
+// arkham stats [--json]
+pub fn show_build_stats(context: &ProjectContext, json: bool) -> Result<(), ArkhamError> {
+    let project_name = read_version_info(context)?
+        .map(|info| info.project_name)
+        .unwrap_or_default();
+    let (versions, without_stats) =
+        collect_stats(read_version_log(context)?, read_build_stats(context)?);
+
+    if json {
+        print_json(&project_name, &versions, without_stats);
+    } else if versions.is_empty() {
+        println!("No build stats recorded yet: 'arkham build' records them.\n");
+    } else {
+        print_table(&project_name, &versions, without_stats);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(artifact_bytes: u64) -> BuildStats {
+        BuildStats {
+            duration_ms: 100,
+            artifact_bytes,
+            warnings: 0,
+            errors: 0,
+        }
+    }
+
+    fn logged(version: &str, stats: Option<BuildStats>) -> LogEntry {
+        LogEntry {
+            version: version.to_string(),
+            log: "log".to_string(),
+            build_date: "2026-01-01 10:00:00".to_string(),
+            built_by: "me".to_string(),
+            commit_id: "snap-1".to_string(),
+            stats,
+        }
+    }
+
+    fn built(version: &str, artifact_bytes: u64) -> BuiltVersion {
+        BuiltVersion {
+            version: version.to_string(),
+            build_date: "2026-01-02 10:00:00".to_string(),
+            stats: stats(artifact_bytes),
+        }
+    }
+
+    #[test]
+    fn versions_bumped_but_never_backed_up_keep_their_stats() {
+        let entries = vec![
+            logged("1.00", None),
+            logged("1.01", Some(stats(1000))),
+            logged("1.02", Some(stats(1500))),
+        ];
+        // 1.02 rebuilt after its backup, 1.03 & 1.04 never backed up
+        let builds = vec![
+            built("1.02", 1600),
+            built("1.03", 2000),
+            built("1.04", 1900),
+        ];
+
+        let (versions, without_stats) = collect_stats(entries, builds);
+        assert_eq!(without_stats, 1);
+
+        let rows: Vec<(&str, u64, Option<i64>, bool)> = versions
+            .iter()
+            .map(|v| {
+                (
+                    v.version.as_str(),
+                    v.stats.artifact_bytes,
+                    v.size_delta,
+                    v.logged,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("1.01", 1000, None, true),
+                ("1.02", 1600, Some(600), true),
+                ("1.03", 2000, Some(400), false),
+                ("1.04", 1900, Some(-100), false),
+            ]
+        );
+        assert_eq!(versions[1].build_date, "2026-01-02 10:00:00");
+    }
+
+    #[test]
+    fn latest_log_entry_of_a_version_wins() {
+        let entries = vec![
+            logged("1.00", Some(stats(1000))),
+            logged("1.01", Some(stats(1200))),
+            logged("1.00", Some(stats(900))),
+        ];
+        let (versions, without_stats) = collect_stats(entries, Vec::new());
+        assert_eq!(without_stats, 0);
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].stats.artifact_bytes, 900);
+        assert_eq!(versions[1].size_delta, Some(300));
+    }
+
+    #[test]
+    fn size_growth_needs_two_versions() {
+        let (one, _) = collect_stats(vec![logged("1.00", Some(stats(1000)))], Vec::new());
+        assert_eq!(size_growth(&one), None);
+        assert_eq!(size_growth(&[]), None);
+
+        let (three, _) = collect_stats(
+            vec![logged("1.00", Some(stats(1000)))],
+            vec![built("1.01", 1600), built("1.02", 700)],
+        );
+        assert_eq!(size_growth(&three), Some((-300, -150)));
+    }
+
+    #[test]
+    fn bytes_durations_and_deltas_read_naturally() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(48213), "47.1 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024 * 1024), "3072.0 GiB");
+        assert_eq!(format_size_delta(1229), "+1.2 KiB");
+        assert_eq!(format_size_delta(-340), "-340 B");
+        assert_eq!(format_size_delta(0), "0 B");
+        assert_eq!(format_duration(850), "850ms");
+        assert_eq!(format_duration(12400), "12.4s");
+    }
+}
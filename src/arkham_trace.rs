@@ -0,0 +1,224 @@
+/*
+ DEBUG TRACING : --debug / ARKHAM_DEBUG=1
+    every external command (args, cwd, exit status, timing) & every state file read / write,
+    one logfmt line per event on stderr, appended to --trace-file / ARKHAM_TRACE_FILE as well
+*/
+use crate::arkham_constants::{ARKHAM_VER, DEBUG_ENV, TRACE_FILE_ENV};
+use crate::arkham_errors::ArkhamError;
+
+use chrono::Local;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+struct Tracer {
+    start: Instant,
+    file: Option<Mutex<File>>,
+}
+
+static TRACER: OnceLock<Tracer> = OnceLock::new();
+
+// ARKHAM_DEBUG set to anything but "", 0 or false
+fn debug_env() -> bool {
+    std::env::var(DEBUG_ENV)
+        .map(|value| !matches!(value.trim(), "" | "0" | "false"))
+        .unwrap_or(false)
+}
+
+/*
+    Startup : turn tracing on if asked to (flag, env var or a trace file), returns whether it is
+    Safe to call once only, later calls keep the first tracer
+*/
+pub fn init_tracing(debug: bool, trace_file: Option<&Path>) -> Result<bool, ArkhamError> {
+    let trace_file: Option<PathBuf> = trace_file
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::var_os(TRACE_FILE_ENV).map(PathBuf::from));
+
+    if !debug && !debug_env() && trace_file.is_none() {
+        return Ok(false);
+    }
+
+    let file = match trace_file {
+        Some(path) => Some(Mutex::new(
+            OpenOptions::new().create(true).append(true).open(&path)?,
+        )),
+        None => None,
+    };
+    let _ = TRACER.set(Tracer {
+        start: Instant::now(),
+        file,
+    });
+
+    trace_event(
+        "start",
+        &[
+            ("arkham", ARKHAM_VER.to_string()),
+            ("pid", std::process::id().to_string()),
+            (
+                "args",
+                std::env::args().skip(1).collect::<Vec<_>>().join(" "),
+            ),
+            ("cwd", current_dir()),
+        ],
+    );
+    Ok(true)
+}
+
+pub fn tracing_enabled() -> bool {
+    TRACER.get().is_some()
+}
+
+// logfmt value : quoted when it has spaces, quotes or '='
+fn field_value(value: &str) -> String {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+        format!("{:?}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn current_dir() -> String {
+    std::env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default()
+}
+
+// [TRACE] +0.012s <event> key=value ...
+pub fn trace_event(event: &str, fields: &[(&str, String)]) {
+    let tracer = match TRACER.get() {
+        Some(tracer) => tracer,
+        None => return,
+    };
+
+    let mut line = format!(
+        "[TRACE] +{:.3}s {}",
+        tracer.start.elapsed().as_secs_f64(),
+        event
+    );
+    for (key, value) in fields {
+        line.push_str(&format!(" {}={}", key, field_value(value)));
+    }
+
+    eprintln!("{}", line);
+    if let Some(file) = &tracer.file {
+        if let Ok(mut file) = file.lock() {
+            let _ = writeln!(
+                file,
+                "{} pid={} {}",
+                Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                std::process::id(),
+                line
+            );
+        }
+    }
+}
+
+// State file read : path & size
+pub fn trace_read(path: &Path) {
+    if !tracing_enabled() {
+        return;
+    }
+    let bytes = path.metadata().map(|meta| meta.len()).unwrap_or(0);
+    trace_event(
+        "read",
+        &[
+            ("file", path.display().to_string()),
+            ("bytes", bytes.to_string()),
+        ],
+    );
+}
+
+// State file write : op = write | append | truncate | stamp ..
+pub fn trace_write(path: &Path, op: &str, bytes: usize) {
+    trace_event(
+        "write",
+        &[
+            ("file", path.display().to_string()),
+            ("op", op.to_string()),
+            ("bytes", bytes.to_string()),
+        ],
+    );
+}
+
+// program + args as one shell-ish string
+pub fn command_line(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|part| {
+            let part = part.to_string_lossy();
+            if part.is_empty() || part.contains(|c: char| c.is_whitespace() || c == '"') {
+                format!("{:?}", part)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// command line & where it runs
+fn describe(cmd: &Command) -> (String, String) {
+    let line = command_line(cmd);
+    let cwd = cmd
+        .get_current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(current_dir);
+    (line, cwd)
+}
+
+fn trace_exit(line: &str, status: Result<ExitStatus, &io::Error>, started: Instant) {
+    let mut fields = vec![("cmd", line.to_string())];
+    match status {
+        Ok(status) => fields.push((
+            "status",
+            status
+                .code()
+                .map(|code| code.to_string())
+                .unwrap_or("signal".to_string()),
+        )),
+        Err(e) => fields.push(("error", e.to_string())),
+    }
+    fields.push(("took_ms", started.elapsed().as_millis().to_string()));
+    trace_event("exit", &fields);
+}
+
+// Command::output(), traced
+pub fn run_output(cmd: &mut Command) -> io::Result<Output> {
+    if !tracing_enabled() {
+        return cmd.output();
+    }
+
+    let (line, cwd) = describe(cmd);
+    trace_event("exec", &[("cmd", line.clone()), ("cwd", cwd)]);
+    let started = Instant::now();
+    let output = cmd.output();
+    trace_exit(&line, output.as_ref().map(|o| o.status), started);
+
+    // Failed : the first stderr line usually says why
+    if let Ok(ref output) = output {
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if let Some(first) = stderr.lines().find(|l| !l.trim().is_empty()) {
+                trace_event("stderr", &[("cmd", line), ("line", first.to_string())]);
+            }
+        }
+    }
+    output
+}
+
+// Command::status() (output straight to the terminal), traced
+pub fn run_status(cmd: &mut Command) -> io::Result<ExitStatus> {
+    if !tracing_enabled() {
+        return cmd.status();
+    }
+
+    let (line, cwd) = describe(cmd);
+    trace_event("exec", &[("cmd", line.clone()), ("cwd", cwd)]);
+    let started = Instant::now();
+    let status = cmd.status();
+    trace_exit(&line, status.as_ref().copied(), started);
+    status
+}
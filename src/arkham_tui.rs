@@ -0,0 +1,439 @@
+/*
+ TERMINAL UI : arkham tui, browse the version history
+    versions newest first (/ searches message, commit, date & author) next to a detail pane :
+    full message & commit, build stats, the kept build & the files changed since the version before
+    d diffs against another version (or the working tree), r runs its build, R restores it
+    diff, run & restore leave the UI while they work & come back after Enter
+*/
+use crate::arkham_constants::MAX_PREV_BUILDS;
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_git::{changed_files, diff_states, restore_to_state};
+use crate::arkham_lock::acquire_lock;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_snapshot::is_snapshot_id;
+use crate::arkham_stats::{format_bytes, format_duration};
+use crate::arkham_trace::run_status;
+use crate::arkham_utility::{color_enabled, get_user_input};
+use crate::arkham_version::{
+    kept_build, read_version_info, read_version_log, LogEntry, ProjectInfo,
+};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::Command;
+
+enum Mode {
+    Browse,
+    Search,
+    PickDiff(usize), // entry diffed, waiting for the other side
+}
+
+// What the key asked for : the ones that need the real terminal run outside the UI
+enum Action {
+    Stay,
+    Quit,
+    Diff(Vec<String>),
+    Run(PathBuf),
+    Restore(String),
+}
+
+struct App<'a> {
+    context: &'a ProjectContext,
+    info: ProjectInfo,
+    entries: Vec<LogEntry>, // newest first
+    visible: Vec<usize>,    // entries matching the search
+    query: String,
+    list: ListState,
+    mode: Mode,
+    detail_scroll: u16,
+    changes: HashMap<usize, Result<Vec<String>, String>>, // per entry, filled when first shown
+    status: String,
+}
+
+// Titles & labels : colored unless --no-color / NO_COLOR
+fn accent() -> Style {
+    match color_enabled() {
+        true => Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        false => Style::new().add_modifier(Modifier::BOLD),
+    }
+}
+
+impl<'a> App<'a> {
+    fn load(context: &'a ProjectContext) -> Result<Self, ArkhamError> {
+        let mut app = App {
+            context,
+            info: read_version_info(context)?.ok_or(ArkhamError::MissingVersionInfo)?,
+            entries: Vec::new(),
+            visible: Vec::new(),
+            query: String::new(),
+            list: ListState::default(),
+            mode: Mode::Browse,
+            detail_scroll: 0,
+            changes: HashMap::new(),
+            status: String::new(),
+        };
+        app.reload()?;
+        Ok(app)
+    }
+
+    // After a restore : the log & info may not be what they were
+    fn reload(&mut self) -> Result<(), ArkhamError> {
+        self.info = read_version_info(self.context)?.ok_or(ArkhamError::MissingVersionInfo)?;
+        self.entries = read_version_log(self.context)?;
+        self.entries.reverse();
+        self.changes.clear();
+        self.filter();
+        Ok(())
+    }
+
+    fn filter(&mut self) {
+        let query = self.query.to_lowercase();
+        self.visible = (0..self.entries.len())
+            .filter(|&i| {
+                let entry = &self.entries[i];
+                [
+                    &entry.version,
+                    &entry.log,
+                    &entry.commit_id,
+                    &entry.build_date,
+                    &entry.built_by,
+                ]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&query))
+            })
+            .collect();
+        self.list.select(if self.visible.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        self.detail_scroll = 0;
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.list
+            .selected()
+            .and_then(|row| self.visible.get(row).copied())
+    }
+
+    fn move_by(&mut self, rows: isize) {
+        if let Some(row) = self.list.selected() {
+            let last = self.visible.len().saturating_sub(1) as isize;
+            self.list
+                .select(Some((row as isize + rows).clamp(0, last) as usize));
+            self.detail_scroll = 0;
+        }
+    }
+
+    // Files changed since the entry logged before this one
+    fn changes_for(&mut self, index: usize) -> &Result<Vec<String>, String> {
+        let (context, entries) = (self.context, &self.entries);
+        self.changes.entry(index).or_insert_with(|| {
+            let previous = entries.get(index + 1).map(|entry| entry.commit_id.as_str());
+            changed_files(context, previous, &entries[index].commit_id).map_err(|e| e.to_string())
+        })
+    }
+
+    fn detail(&mut self, index: usize) -> Vec<Line<'static>> {
+        let entry = self.entries[index].clone();
+        let label = |name: &str| Span::styled(format!("{:<10}", name), accent());
+        let mut lines = Vec::new();
+
+        let current = match entry.version == self.info.current_version {
+            true => " (current)",
+            false => "",
+        };
+        lines.push(Line::from(vec![
+            label("Version"),
+            Span::raw(format!("{}{}", entry.version, current)),
+        ]));
+        lines.push(Line::from(vec![
+            label("Logged"),
+            Span::raw(format!("{} by {}", entry.build_date, entry.built_by)),
+        ]));
+        lines.push(Line::from(vec![
+            label(match is_snapshot_id(&entry.commit_id) {
+                true => "Snapshot",
+                false => "Commit",
+            }),
+            Span::raw(entry.commit_id.clone()),
+        ]));
+        lines.push(Line::from(vec![
+            label("Build"),
+            Span::raw(match entry.stats {
+                Some(stats) => format!(
+                    "{}, {}, {} warning(s), {} error(s)",
+                    format_duration(stats.duration_ms),
+                    format_bytes(stats.artifact_bytes),
+                    stats.warnings,
+                    stats.errors
+                ),
+                None => "no stats (logged before arkham kept them)".to_string(),
+            }),
+        ]));
+        lines.push(Line::from(vec![
+            label("Kept"),
+            Span::raw(
+                match kept_build(self.context, &self.info.project_name, &entry.version) {
+                    Some(path) => path
+                        .strip_prefix(&self.context.root)
+                        .unwrap_or(&path)
+                        .display()
+                        .to_string(),
+                    None => format!("no (only the last {} builds are kept)", MAX_PREV_BUILDS),
+                },
+            ),
+        ]));
+
+        lines.push(Line::default());
+        lines.push(Line::styled("Message", accent()));
+        for message_line in entry.log.lines() {
+            lines.push(Line::raw(format!("  {}", message_line)));
+        }
+
+        lines.push(Line::default());
+        let since = match self.entries.get(index + 1) {
+            Some(previous) => format!("Changed since {}", previous.version),
+            None => "Files (first logged version)".to_string(),
+        };
+        match self.changes_for(index) {
+            Ok(files) => {
+                lines.push(Line::styled(
+                    format!("{} ({})", since, files.len()),
+                    accent(),
+                ));
+                lines.extend(files.iter().map(|file| Line::raw(format!("  {}", file))));
+            }
+            Err(e) => {
+                lines.push(Line::styled(since, accent()));
+                lines.push(Line::raw(format!("  {}", e)));
+            }
+        }
+        lines
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main_area, help_area] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main_area);
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&i| {
+                let entry = &self.entries[i];
+                let first_line = entry.log.lines().next().unwrap_or_default();
+                ListItem::new(format!("{:<7} {}", entry.version, first_line))
+            })
+            .collect();
+        let list_title = match (&self.mode, self.query.is_empty()) {
+            (Mode::Search, _) => format!(" Search: {}_ ", self.query),
+            (_, false) => format!(" Versions matching '{}' ", self.query),
+            (_, true) => format!(" {} versions ", self.info.project_name),
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(list_title))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        let detail = match self.selected() {
+            Some(index) => self.detail(index),
+            None => vec![Line::raw("No version matches the search")],
+        };
+        let detail = Paragraph::new(detail)
+            .block(Block::default().borders(Borders::ALL).title(" Details "))
+            .wrap(Wrap { trim: false })
+            .scroll((self.detail_scroll, 0));
+        frame.render_widget(detail, detail_area);
+
+        let help = match (&self.mode, self.status.is_empty()) {
+            (Mode::Search, _) => {
+                "type to search, Enter keeps the filter, Esc clears it".to_string()
+            }
+            (Mode::PickDiff(index), _) => format!(
+                "diff {} against: pick a version & Enter, w = working tree, Esc = cancel",
+                self.entries[*index].version
+            ),
+            (Mode::Browse, false) => self.status.clone(),
+            (Mode::Browse, true) => {
+                "↑↓ select  PgUp/PgDn scroll  / search  d diff  r run build  R restore  q quit"
+                    .to_string()
+            }
+        };
+        frame.render_widget(Paragraph::new(help).style(accent()), help_area);
+    }
+
+    fn on_key(&mut self, key: KeyEvent) -> Action {
+        self.status.clear();
+        match self.mode {
+            Mode::Search => {
+                match key.code {
+                    KeyCode::Enter => self.mode = Mode::Browse,
+                    KeyCode::Esc => {
+                        self.query.clear();
+                        self.mode = Mode::Browse;
+                        self.filter();
+                    }
+                    KeyCode::Backspace => {
+                        self.query.pop();
+                        self.filter();
+                    }
+                    KeyCode::Char(c) => {
+                        self.query.push(c);
+                        self.filter();
+                    }
+                    _ => {}
+                }
+                Action::Stay
+            }
+            Mode::PickDiff(from) => match key.code {
+                KeyCode::Esc => {
+                    self.mode = Mode::Browse;
+                    Action::Stay
+                }
+                KeyCode::Char('w') => {
+                    self.mode = Mode::Browse;
+                    Action::Diff(vec![self.entries[from].version.clone()])
+                }
+                KeyCode::Enter => match self.selected() {
+                    Some(to) if self.entries[to].version != self.entries[from].version => {
+                        self.mode = Mode::Browse;
+                        // older one first : the diff reads as what changed since
+                        let (older, newer) = (from.max(to), from.min(to));
+                        Action::Diff(vec![
+                            self.entries[older].version.clone(),
+                            self.entries[newer].version.clone(),
+                        ])
+                    }
+                    _ => Action::Stay,
+                },
+                _ => self.navigate(key.code),
+            },
+            Mode::Browse => match key.code {
+                KeyCode::Char('q') => Action::Quit,
+                KeyCode::Esc if self.query.is_empty() => Action::Quit,
+                KeyCode::Esc => {
+                    self.query.clear();
+                    self.filter();
+                    Action::Stay
+                }
+                KeyCode::Char('/') => {
+                    self.mode = Mode::Search;
+                    Action::Stay
+                }
+                KeyCode::Char('d') => {
+                    if let Some(index) = self.selected() {
+                        self.mode = Mode::PickDiff(index);
+                    }
+                    Action::Stay
+                }
+                KeyCode::Char('r') => match self.selected() {
+                    Some(index) => {
+                        let version = &self.entries[index].version;
+                        match kept_build(self.context, &self.info.project_name, version) {
+                            Some(path) => Action::Run(path),
+                            None => {
+                                self.status = format!(
+                                    "No build of {} kept (only the last {} are)",
+                                    version, MAX_PREV_BUILDS
+                                );
+                                Action::Stay
+                            }
+                        }
+                    }
+                    None => Action::Stay,
+                },
+                KeyCode::Char('R') => match self.selected() {
+                    Some(index) => Action::Restore(self.entries[index].version.clone()),
+                    None => Action::Stay,
+                },
+                code => self.navigate(code),
+            },
+        }
+    }
+
+    fn navigate(&mut self, code: KeyCode) -> Action {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Home | KeyCode::Char('g') => self.move_by(isize::MIN / 2),
+            KeyCode::End | KeyCode::Char('G') => self.move_by(isize::MAX / 2),
+            KeyCode::PageUp => self.detail_scroll = self.detail_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.detail_scroll = self.detail_scroll.saturating_add(10),
+            _ => {}
+        }
+        Action::Stay
+    }
+
+    // Out of the UI : diff, run or restore on the plain terminal
+    fn perform(&mut self, action: Action) -> Result<(), ArkhamError> {
+        match action {
+            Action::Diff(versions) => diff_states(self.context, &versions),
+            Action::Run(path) => {
+                println!("Running {}\n", path.display());
+                let status = run_status(Command::new(&path).current_dir(&self.context.root))?;
+                println!("\n{} exited with {}", path.display(), status);
+                Ok(())
+            }
+            Action::Restore(version) => {
+                let _lock = acquire_lock(self.context, "restore", false)?;
+                restore_to_state(self.context, &version)?;
+                self.reload()
+            }
+            Action::Stay | Action::Quit => Ok(()),
+        }
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> Result<(), ArkhamError> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        match app.on_key(key) {
+            Action::Stay => {}
+            Action::Quit => return Ok(()),
+            action => {
+                ratatui::restore();
+                if let Err(e) = app.perform(action) {
+                    println!("Error: {}", e);
+                }
+                get_user_input("\nPress Enter to return to arkham tui")?;
+                *terminal = ratatui::init();
+            }
+        }
+    }
+}
+
+// arkham tui
+pub fn browse_versions(context: &ProjectContext) -> Result<(), ArkhamError> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Err(ArkhamError::TuiError(
+            "needs a terminal, 'arkham archives' prints the version history instead".to_string(),
+        ));
+    }
+
+    let mut app = App::load(context)?;
+    if app.entries.is_empty() {
+        println!("No Version logs found!\n");
+        return Ok(());
+    }
+
+    // ratatui::init restores the terminal on a panic too
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
@@ -1,90 +1,137 @@
 use crate::arkham_constants::*;
 use crate::arkham_errors::ArkhamError;
+use crate::arkham_render::{header_lines, terminal_width, Tone};
 use figlet_rs::FIGfont;
 use std::io;
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Output settings, from the global flags (--quiet / --verbose / --no-color)
+static QUIET: AtomicBool = AtomicBool::new(false);
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+static NO_COLOR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_output_flags(quiet: bool, verbose: bool, no_color: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    VERBOSE.store(verbose, Ordering::Relaxed);
+    NO_COLOR.store(no_color, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+// --no-color or NO_COLOR set (any value, see no-color.org)
+pub fn color_enabled() -> bool {
+    !NO_COLOR.load(Ordering::Relaxed) && std::env::var_os("NO_COLOR").is_none()
+}
 
 // Utility Methods
-pub(crate) fn ascii_title_print() {
+pub fn ascii_title_print() {
     let standard_font = FIGfont::standard().unwrap();
-    let figure = standard_font
-        .convert(&format!("{}", ARKHAM_ASCII_LOGO))
-        .unwrap();
+    let figure = standard_font.convert(ARKHAM_ASCII_LOGO).unwrap();
     println!("{}", figure);
 }
 
-pub(crate) fn insert_separator() {
+pub fn insert_separator() {
     let sep_char = String::from(DISPLAY_HEADER_CHAR); // U+2324
-    println!("{}", sep_char.repeat(40)); // line length is hardcoded
+    let width = terminal_width().unwrap_or(40).min(40);
+    println!("{}", sep_char.repeat(width));
 }
 
-pub(crate) fn display_header_msg(message: &str) {
-    let width = 80;
-    let padding = 2;
-
-    // Box drawing
-    let top_left = "╔";
-    let top_right = "╗";
-    let bottom_left = "╚";
-    let bottom_right = "╝";
-    let horizontal = "═";
-    let vertical = "║";
-
-    println!("{}{}{}", top_left, horizontal.repeat(width - 2), top_right);
-
-    for line in message.lines() {
-        let content_width = width - 4; // -4 for borders and minimum spacing
-        let padding_total = content_width - line.len();
-        let padding_left = padding_total / 2;
-        let padding_right = padding_total - padding_left;
-
-        println!(
-            "{}{}{}{}{}",
-            vertical,
-            " ".repeat(padding_left + 1),
-            line,
-            " ".repeat(padding_right + 1),
-            vertical
-        );
+fn print_header(message: &str, tone: Tone) {
+    // --quiet : banners go, results & errors stay
+    if is_quiet() {
+        return;
+    }
+    for line in header_lines(message, tone) {
+        println!("{}", line);
     }
+}
 
-    //  bottom border
-    println!(
-        "{}{}{}",
-        bottom_left,
-        horizontal.repeat(width - 2),
-        bottom_right
-    );
+// Boxed on a terminal (fits its width, long lines wrapped), "== message ==" when piped / NO_COLOR
+pub fn display_header_msg(message: &str) {
+    print_header(message, Tone::Plain);
+}
+
+// Same, green
+pub fn display_success_msg(message: &str) {
+    print_header(message, Tone::Success);
+}
+
+// Same, red
+pub fn display_error_msg(message: &str) {
+    print_header(message, Tone::Error);
 }
 
 // ./arkham help
-pub(crate) fn help_me() {
+pub fn help_me() {
     ascii_title_print();
     display_header_msg(&format!(
         "Arkham Versioning Protocol: v{}\n- Author: {}",
         ARKHAM_VER, AUTHOR
     ));
-    println!("Usage: arkham [OPTION] [ARGS]\n");
-    println!("Options:");
+    println!("Usage: arkham [FLAGS] <COMMAND> [ARGS]\n");
+    println!("Commands:");
     println!("   help                           ==> Display this general help information");
     println!("   help [TOPIC]                   ==> Display help for a specific topic");
+    println!("   init [init-flags]              ==> Setup Arkham Versioning for the project");
     println!("   build [build-flags]            ==> Setup Arkham Versioning & Build the project");
+    println!("   watch [--debounce MS]          ==> Rebuild on every change to the project tree (no version bump)");
     println!("   clean                          ==> Clean up the project");
-    println!("   backup                         ==> Save the current project state via Git");
+    println!("   backup [--backend git|tarball] ==> Save the current project state (Git or tarball snapshot)");
+    println!("   restore [VERSION]              ==> Revert project files to a saved version state");
+    println!("   diff [VERSION] [VERSION]       ==> Compare a saved version with another or the working tree");
     println!("   archives                       ==> Display all version logs");
     println!("   archive-entry [VERSIONS...]    ==> Display logs for specific versions");
-    println!("   app-status                     ==> Display Current App Information\n");
+    println!("   tui                            ==> Browse, search, diff, run & restore versions interactively");
+    println!("   verify BINARY [--key PEM]      ==> Check a build's signature & embedded version");
+    println!("   app-status                     ==> Display Current App Information");
+    println!("   stats [--json]                 ==> Build time, size & warning trends across built versions");
+    println!("   doctor                         ==> Check version info, logs, symlinks & prev_builds for problems");
+    println!(
+        "   repair                         ==> Re-quote or quarantine malformed version log rows"
+    );
+    println!("   workspace                      ==> Display every component of a workspace");
+    println!("   relocate [--from OLD_ROOT]     ==> Fix project root & symlinks after moving the project\n");
+    println!("Flags (any command):");
+    println!("   -p, --component COMPONENT      ==> Workspace component to work on");
+    println!("   -C, --project-dir DIR          ==> Run as if arkham was started in DIR");
+    println!("   -q, --quiet                    ==> Only print results & errors (no banners)");
+    println!("   -v, --verbose                  ==> Print extra detail about what arkham is doing");
+    println!("   --debug                        ==> Trace commands run & state files read / written ({}=1)", DEBUG_ENV);
+    println!(
+        "   --trace-file FILE              ==> Append the trace to FILE as well ({})",
+        TRACE_FILE_ENV
+    );
+    println!("   --no-color                     ==> Plain output, no colors or boxes (NO_COLOR is honoured too)");
+    println!("   --no-wait                      ==> Fail instead of waiting for the project lock");
+    println!("   -h, --help / -V, --version     ==> Help for any command / arkham version\n");
+    println!("build skips make & stamping when nothing changed since the last build; --force rebuilds anyway");
+    println!(
+        "build, backup & clean take --dry-run: show the commands & file changes, touch nothing"
+    );
+    println!("build, backup, restore, relocate & repair take the project lock ({}); --no-wait fails instead of waiting\n", LOCK_FILE);
     println!("Topics for specific help:");
+    println!("   init       ==> Information about project setup");
+    println!("   workspace  ==> Information about monorepo workspaces");
     println!("   version    ==> Information about versioning");
-    println!("   git        ==> Information about Git integration\n");
+    println!("   git        ==> Information about Git integration");
+    println!("   hooks      ==> Information about build / bump / backup hooks\n");
     println!("Examples:");
     println!(" ./arkham help version");
+    println!(" ./arkham build --help");
     println!(" ./arkham backup");
     println!(" ./arkham archive-entry 3.51 3.52");
     println!(" ./arkham archives");
+    println!(" ./arkham stats --json");
 }
 
-pub(crate) fn help_with(topic: &str) {
+pub fn help_with(topic: &str) {
     match topic {
         "version" => {
             display_header_msg("Arkham Help: Versioning");
@@ -92,50 +139,172 @@ pub(crate) fn help_with(topic: &str) {
             println!("- Versions are stored in .version.info");
             println!("- Format: MAJOR.MINOR (e.g., 3.51)");
             println!("- Version info is embedded in the executable, to check : run \'strings <executable> | tail -n 4\'");
+            println!("- Extra artifacts (artifacts=main,boot.img,lib/libfoo.so in .version.info) share the version");
+            println!("- Each build is stamped, checksummed (<name>_v_<version>.sha256) & symlinked as one unit");
+            println!("- State files carry a schema version; older ones are upgraded in place (kept as <file>.schema<N>.bak)");
             println!();
             println!("To update version after a successful build:");
             println!("- Choose 'yes | y' when prompted");
             println!("- Select MAJOR (1) or MINOR (0) update");
+            println!("- Nothing changed since the last build (sources & build args) => 'up to date', no build;");
+            println!("  ./arkham build --force rebuilds anyway");
+            println!();
+            println!(
+                "Size limits ({}), checked before the build is stamped:",
+                CONFIG_FILE
+            );
+            println!("  size_budget=64K          # max size of each stamped artifact");
+            println!("  size_growth_limit=5%     # max growth of each artifact over its previous build (or a size: 4K)");
+            println!(
+                "  size_gate=fail           # fail: no version bump (default) | warn: report only"
+            );
             println!();
+            println!(
+                "Signed builds ({}), every stamped build gets <build>.sig next to it:",
+                CONFIG_FILE
+            );
+            println!("  signing_key=arkham.pem   # ed25519 private key: openssl genpkey -algorithm ed25519 -out arkham.pem");
+            println!("  verify_key=arkham.pub    # public key for verify: openssl pkey -in arkham.pem -pubout -out arkham.pub");
+            println!(" ./arkham verify main      # signature OK & stamp matches => Verified");
+            println!();
+            println!("Build stats (time, size of all artifacts, warnings) of every version are kept in .version.stats");
+            println!("as soon as it is built: ./arkham stats shows versions never backed up too");
+            println!();
+            println!("To see build time, size & warning trends:");
+            println!(" ./arkham stats");
             println!("To see all version logs:");
             println!(" ./arkham archives");
             println!("To see specific version logs:");
             println!(" ./arkham archive-entry 3.51");
         }
+        "init" => {
+            display_header_msg("Arkham Help: Project Setup");
+            println!("arkham init sets up versioning; anything not passed as a flag is asked for:");
+            println!("  --name <EXECUTABLE>       executable produced by the build");
+            println!("  --version <X.YY>          starting version (validated)");
+            println!(
+                "  --root <DIR>              project root (default: nearest dir with a build file)"
+            );
+            println!("  --build-system <SYSTEM>   make | cmake | cargo (default: detected)");
+            println!("  --backend <BACKEND>       backup backend: git | tarball (default: kept, else git; needs the config)");
+            println!("  --artifact <PATH>         extra build output versioned with the executable (repeatable)");
+            println!("  --config | --no-config    write {} or not", CONFIG_FILE);
+            println!(
+                "  --force                   overwrite an existing {}",
+                VERSION_INFO_FILE
+            );
+            println!();
+            println!("Examples:");
+            println!(" ./arkham init");
+            println!(" ./arkham init --name CookieUFS --version 3.53 --config");
+        }
+        "workspace" => {
+            display_header_msg("Arkham Help: Workspaces");
+            println!(
+                "A {} at the repo root lists independently versioned components:",
+                WORKSPACE_FILE
+            );
+            println!();
+            println!("  [bootloader]");
+            println!("  target=bootloader                        # build target (make bootloader)");
+            println!("  artifacts=build/boot.bin                 # versioned outputs");
+            println!("  version_file=.version.bootloader.info    # optional, this is the default");
+            println!("  log_file=.version.bootloader.log         # optional, this is the default");
+            println!();
+            println!("Pick a component with -p (for build, before the build args):");
+            println!(" ./arkham init -p bootloader --name boot.bin --version 1.00");
+            println!(" ./arkham build -p bootloader V=1");
+            println!(" ./arkham archives -p bootloader");
+            println!(" ./arkham app-status -p bootloader");
+            println!(" ./arkham workspace");
+        }
         "git" => {
             display_header_msg("Arkham Help: Git Integration");
             println!("Arkham provides basic Git integration:");
             println!("- backup:             Save current changes");
-            println!("- restore [VERSION]:  Revert Project to previous version state [Not Implemented Yet]");
+            println!("- restore [VERSION]:  Revert Project to previous version state");
+            println!("- diff [VERSION]:     Show changes since a saved version");
+            println!();
+            println!("Log messages:");
+            println!("- backup opens $VISUAL / $EDITOR on a template with the commits since the last logged version");
+            println!("- lines starting with '#' are dropped, an empty message aborts the backup");
+            println!("- no editor, no terminal or --no-edit: a one line prompt instead");
+            println!();
+            println!("Git-free backups:");
+            println!(
+                "- set 'backup_backend=tarball' in {} (or pass --backend tarball)",
+                CONFIG_FILE
+            );
+            println!(
+                "- each backup is archived to {}/<snapshot-id>.tar.gz",
+                SNAPSHOT_DIR
+            );
+            println!("- the snapshot ID is logged in place of the commit ID");
+            println!(
+                "- protected files, .gitignore and {} entries are never archived",
+                IGNORE_FILE
+            );
             println!();
             println!("Examples:");
             println!(" ./arkham backup");
+            println!(" ./arkham backup --backend tarball");
+            println!(" ./arkham backup --no-edit");
             println!(" ./arkham restore 3.51");
+            println!(" ./arkham diff 3.51 3.52");
+        }
+        "hooks" => {
+            display_header_msg("Arkham Help: Hooks");
+            println!(
+                "Shell commands in {} run at each stage (sh -c, from the project root):",
+                CONFIG_FILE
+            );
+            println!();
+            println!("  hook.pre_build=./scripts/check-deps.sh");
+            println!("  hook.post_build=make test                  # failing => no version bump");
+            println!(
+                "  hook.pre_bump=git diff --quiet             # failing => build kept, no bump"
+            );
+            println!("  hook.post_bump=./scripts/upload.sh \"$ARKHAM_ARTIFACT\"");
+            println!("  hook.pre_backup=...");
+            println!("  hook.post_backup=./scripts/notify.sh \"$ARKHAM_NEW_VERSION\" \"$ARKHAM_COMMIT_ID\"");
+            println!();
+            println!("- A stage may be listed several times, the commands run in order");
+            println!("- A failing pre_* hook aborts the operation, a failing post_* one makes arkham exit 1");
+            println!("- Environment: ARKHAM_HOOK, ARKHAM_PROJECT, ARKHAM_ROOT, ARKHAM_COMPONENT,");
+            println!("  ARKHAM_OLD_VERSION, ARKHAM_NEW_VERSION, ARKHAM_ARTIFACT, ARKHAM_ARTIFACTS (':' separated),");
+            println!("  ARKHAM_COMMIT_ID (post_backup)");
+            println!(
+                "- arkham commands run by a hook share the project lock instead of waiting for it"
+            );
         }
         _ => {
             println!("Unknown help topic: {}", topic);
-            println!("Available topics: version, git");
+            println!("Available topics: init, version, workspace, git, hooks (or any command)");
         }
     }
 }
 
-pub(crate) fn debug_log(debug_message: &str) {
+// --verbose only
+pub fn debug_log(debug_message: &str) {
+    if !is_verbose() {
+        return;
+    }
     println!("[DEBUG]: {}", debug_message);
 }
 
-pub(crate) fn not_implemented_yet(debug_message: &str) {
+pub fn not_implemented_yet(debug_message: &str) {
     display_header_msg(&format!("[not_implemented_yet]: {}", debug_message));
 }
 
 // Helper function to read user input
-pub(crate) fn get_user_input(prompt: &str) -> Result<String, ArkhamError> {
+pub fn get_user_input(prompt: &str) -> Result<String, ArkhamError> {
     print!("{}", prompt);
-    io::stdout().flush().map_err(|e| ArkhamError::IoError(e))?;
+    io::stdout().flush().map_err(ArkhamError::IoError)?;
 
     let mut input = String::new();
     io::stdin()
         .read_line(&mut input)
-        .map_err(|e| ArkhamError::IoError(e))?;
+        .map_err(ArkhamError::IoError)?;
 
     Ok(input.trim().to_string())
 }
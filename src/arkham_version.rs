@@ -3,48 +3,115 @@
 */
 extern crate tabled;
 
-use crate::arkham_constants::{VersionRecord, VERSION_INFO_FILE, VERSION_LOGS_FILE};
+use crate::arkham_config::{read_config, resolve_build_system, BuildSystem, Hook};
+use crate::arkham_constants::{
+    VersionRecord, BUILD_SIDECAR_EXTENSIONS, BUILD_STATS_HEADER, CHECKSUM_EXTENSION,
+    INFO_SCHEMA_VERSION, LOG_FIELDS, MAX_PREV_BUILDS, SIGNATURE_EXTENSION, TEMP_FILE_SUFFIX,
+    VERSION_INFO_FILE,
+};
+use crate::arkham_dryrun::DryRun;
 use crate::arkham_errors::*;
-use crate::arkham_utility::{debug_log, display_header_msg, insert_separator};
+use crate::arkham_fingerprint::{build_fingerprint, is_up_to_date};
+use crate::arkham_fs::{append_record, temp_path, write_atomic, FsTransaction};
+use crate::arkham_hooks::{plan_hooks, run_hooks, HookEnv};
+use crate::arkham_init::{init_project, InitOptions};
+use crate::arkham_project::ProjectContext;
+use crate::arkham_render::{fit_to_terminal, paint, Tone};
+use crate::arkham_schema::{check_schema, log_preamble, parse_schema, read_log_lines};
+use crate::arkham_signing::{configured_signing_key, key_id, sign_file, signature_path};
+use crate::arkham_size::{check_size, enforce_size_gate, ensure_artifacts_built, plan_size_gate};
+use crate::arkham_stats::format_duration;
+use crate::arkham_trace::{run_output, trace_event, trace_read, trace_write};
+use crate::arkham_utility::{
+    debug_log, display_error_msg, display_header_msg, display_success_msg, insert_separator,
+};
 
 use chrono::Local;
+use ed25519_dalek::SigningKey;
+use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::Write;
-use std::io::{BufRead, BufReader, ErrorKind};
+use std::io::{BufRead, BufReader};
 use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{Duration, Instant};
 use std::{fs, io};
 use tabled::{
-    settings::{object::Segment, Alignment, Modify, Padding, Style, Width},
+    settings::{object::Segment, Alignment, Modify, Padding, Style},
     Table,
 };
-#[derive(Debug)]
-pub(crate) struct ProjectInfo {
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ProjectInfo {
     pub project_name: String,
     pub current_version: String,
     pub project_root: String,
+    pub artifacts: Vec<String>, // relative to the project root, versioned as one unit
+    pub last_build: Option<BuildStats>, // latest build of current_version, logged with the next backup
+    pub build_fingerprint: Option<String>, // what that build was built from (see arkham_fingerprint)
 }
 impl ProjectInfo {
-    // Constructor
-    fn new(name: String, version: String, root: String) -> Self {
+    // Constructor : the executable itself is the only artifact
+    pub fn new(name: String, version: String, root: String) -> Self {
         ProjectInfo {
+            artifacts: vec![name.clone()],
             project_name: name,
             current_version: version,
             project_root: root,
+            last_build: None,
+            build_fingerprint: None,
         }
     }
 }
 
+// What one build cost & produced
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[non_exhaustive]
+pub struct BuildStats {
+    pub duration_ms: u64,
+    pub artifact_bytes: u64, // every stamped artifact, summed
+    pub warnings: u64,
+    pub errors: u64,
+}
+
+impl BuildStats {
+    // .version.log columns : build_ms,artifact_bytes,warnings,errors (empty when not known)
+    pub fn log_fields(stats: Option<&BuildStats>) -> [String; 4] {
+        match stats {
+            Some(stats) => [
+                stats.duration_ms.to_string(),
+                stats.artifact_bytes.to_string(),
+                stats.warnings.to_string(),
+                stats.errors.to_string(),
+            ],
+            None => Default::default(),
+        }
+    }
+
+    // Back from those columns : None unless all four are there
+    pub fn from_log_fields(fields: &[String]) -> Option<BuildStats> {
+        let mut numbers = fields.iter().map(|field| field.parse::<u64>().ok());
+        Some(BuildStats {
+            duration_ms: numbers.next()??,
+            artifact_bytes: numbers.next()??,
+            warnings: numbers.next()??,
+            errors: numbers.next()??,
+        })
+    }
+}
+
 // Reading Version Info From File : .version.info
-pub(crate) fn read_version_info() -> Result<Option<ProjectInfo>, ArkhamError> {
+pub fn read_version_info(context: &ProjectContext) -> Result<Option<ProjectInfo>, ArkhamError> {
     // Check if File Exists
-    if !Path::new(VERSION_INFO_FILE).exists() {
+    let info_path = context.info_file();
+    if !info_path.exists() {
         return Ok(None);
     }
 
     // Reading the file
-    let info_file = match File::open(VERSION_INFO_FILE) {
+    trace_read(&info_path);
+    let info_file = match File::open(&info_path) {
         Ok(file) => file,
         Err(er) => return Err(ArkhamError::from(er)),
     };
@@ -54,6 +121,10 @@ pub(crate) fn read_version_info() -> Result<Option<ProjectInfo>, ArkhamError> {
     let mut project_name = String::new();
     let mut current_version = String::new();
     let mut project_root = String::new();
+    let mut artifacts: Vec<String> = Vec::new();
+    let mut last_build = BuildStats::default();
+    let mut has_last_build = false;
+    let mut build_fingerprint = None;
 
     // Read from Reader
     for line in reader.lines() {
@@ -74,6 +145,30 @@ pub(crate) fn read_version_info() -> Result<Option<ProjectInfo>, ArkhamError> {
             "project_name" => project_name = String::from(parse_info[1]),
             "current_version" => current_version = String::from(parse_info[1]),
             "project_root" => project_root = String::from(parse_info[1]),
+            "artifacts" => artifacts = parse_artifact_list(parse_info[1]),
+            "last_build_fingerprint" => build_fingerprint = Some(parse_info[1].trim().to_string()),
+            key @ ("last_build_ms"
+            | "last_build_bytes"
+            | "last_build_warnings"
+            | "last_build_errors") => {
+                let value = parse_info[1].trim().parse::<u64>().map_err(|_| {
+                    ArkhamError::CorruptVersionInfo(format!(
+                        "Invalid {} '{}' in {}",
+                        key, parse_info[1], VERSION_INFO_FILE
+                    ))
+                })?;
+                match key {
+                    "last_build_ms" => last_build.duration_ms = value,
+                    "last_build_bytes" => last_build.artifact_bytes = value,
+                    "last_build_warnings" => last_build.warnings = value,
+                    _ => last_build.errors = value,
+                }
+                has_last_build = true;
+            }
+            "schema_version" => {
+                let schema = parse_schema(&info_path, parse_info[1])?;
+                check_schema(&info_path, schema, INFO_SCHEMA_VERSION)?;
+            }
             _ => {}
         }
     }
@@ -84,94 +179,158 @@ pub(crate) fn read_version_info() -> Result<Option<ProjectInfo>, ArkhamError> {
             VERSION_INFO_FILE
         )))
     } else {
-        Ok(Some(ProjectInfo::new(
-            project_name,
-            current_version,
-            project_root,
-        )))
+        let mut info = ProjectInfo::new(project_name, current_version, project_root);
+        if !artifacts.is_empty() {
+            info.artifacts = artifacts;
+        }
+        if has_last_build {
+            info.last_build = Some(last_build);
+        }
+        info.build_fingerprint = build_fingerprint;
+        // Workspace manifest has the last word on a component's artifacts
+        if let Some(ref component) = context.component {
+            if !component.artifacts.is_empty() {
+                info.artifacts = component.artifacts.clone();
+            }
+        }
+        Ok(Some(info))
+    }
+}
+
+// artifacts=main,boot/boot.img,lib/libfoo.so
+pub fn parse_artifact_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|artifact| artifact.trim().to_string())
+        .filter(|artifact| !artifact.is_empty())
+        .collect()
+}
+
+// .version.info contents for info
+fn version_info_contents(info: &ProjectInfo) -> String {
+    let mut contents = format!(
+        "schema_version={}\nproject_name={}\ncurrent_version={}\nproject_root={}\n",
+        INFO_SCHEMA_VERSION, info.project_name, info.current_version, info.project_root
+    );
+    // single-executable projects don't need the artifacts line
+    if info.artifacts != [info.project_name.clone()] {
+        contents.push_str(&format!("artifacts={}\n", info.artifacts.join(",")));
+    }
+    if let Some(stats) = &info.last_build {
+        contents.push_str(&format!(
+            "last_build_ms={}\nlast_build_bytes={}\nlast_build_warnings={}\nlast_build_errors={}\n",
+            stats.duration_ms, stats.artifact_bytes, stats.warnings, stats.errors
+        ));
     }
+    if let Some(fingerprint) = &info.build_fingerprint {
+        contents.push_str(&format!("last_build_fingerprint={}\n", fingerprint));
+    }
+    contents
 }
 
 // Write Version Info to File
-pub(crate) fn write_version_info(
-    project_name: &str,
-    current_version: &str,
-    project_root: &str,
-) -> Result<(), ArkhamError> {
+pub fn write_version_info(context: &ProjectContext, info: &ProjectInfo) -> Result<(), ArkhamError> {
     // debug_log(&format!("Adding Versioning info to {}", VERSION_INFO_FILE));
 
-    // Create File : will create if it doesn't exist & truncate if it does
-    let mut info_file = File::create(VERSION_INFO_FILE)?;
-    // Start writing to file
-    writeln!(info_file, "project_name={}", project_name)?;
-    writeln!(info_file, "current_version={}", current_version)?;
-    writeln!(info_file, "project_root={}", project_root)?;
-    Ok(())
+    // Build the whole file first, then swap it in : a crash never leaves it half written
+    write_atomic(&context.info_file(), version_info_contents(info).as_bytes())
 }
 
-// Update Version in File
-pub(crate) fn update_version(
-    current_version: &str,
-    update_type: &str,
-) -> Result<String, ArkhamError> {
-    // parse and fetch current version
-    let parse_info: Vec<&str> = current_version.splitn(2, ' ').collect();
-    if parse_info.len() != 2 {
-        return Err(ArkhamError::InvalidVersion(current_version.to_string()));
-    }
+// Next version : MAJOR (1) => X+1.00, MINOR (0) => X.YY+1
+pub fn update_version(current_version: &str, update_type: &str) -> Result<String, ArkhamError> {
+    validate_version(current_version)?;
 
-    // Check major|minor version
-    let major_ver: i32 = parse_info[0]
+    // parse and fetch current version
+    let parse_info: Vec<&str> = current_version.splitn(2, '.').collect();
+    let major_ver: u32 = parse_info[0]
         .parse()
-        .map_err(|_| io::Error::new(ErrorKind::InvalidData, "Invalid major version"))?;
-    let minor_ver: i32 = parse_info[1]
+        .map_err(|_| ArkhamError::InvalidVersion(current_version.to_string()))?;
+    let minor_ver: u32 = parse_info[1]
         .parse()
-        .map_err(|_| io::Error::new(ErrorKind::InvalidData, "Invalid minor version"))?;
+        .map_err(|_| ArkhamError::InvalidVersion(current_version.to_string()))?;
 
     // Update to new version
-    let new_version = match update_type {
-        "major" | "1" => format!("{}.00", major_ver + 1),
-        "minor" | "0" => format!("{}.{:02}", major_ver, minor_ver + 1),
-        _ => current_version.to_string(), // if nothing then stay on the same version
-    };
-    Ok(new_version)
+    match update_type.trim().to_lowercase().as_str() {
+        "major" | "1" => Ok(format!("{}.00", major_ver + 1)),
+        "minor" | "0" => Ok(format!("{}.{:02}", major_ver, minor_ver + 1)),
+        _ => Err(ArkhamError::InvalidVersion(
+            "Invalid update type".to_string(),
+        )),
+    }
 }
 
 // Log Version Info
-pub(crate) fn log_version(
+pub fn log_version(
+    context: &ProjectContext,
     version_log: &str,
     built_by: &str,
     commit_id: &str,
 ) -> Result<(), ArkhamError> {
-    let info = read_version_info()?.ok_or(ArkhamError::MissingVersionInfo)?;
+    let info = read_version_info(context)?.ok_or(ArkhamError::MissingVersionInfo)?;
 
     let build_date = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
-    if !Path::new(VERSION_LOGS_FILE).exists() {
-        let mut log_version_file = File::create(VERSION_LOGS_FILE)?;
-        writeln!(
-            log_version_file,
-            "version_name,version_log,build_date,built_by,commit_id"
-        )?; // Write Header
+    let log_path = context.log_file();
+    if !log_path.exists() {
+        write_atomic(&log_path, log_preamble().as_bytes())?;
+        // Write Header
     }
 
-    // Open file in append mode
-    let mut log_file = fs::OpenOptions::new()
-        .append(true)
-        .open(VERSION_LOGS_FILE)?;
-
-    // Write to the file : CSV format
-    writeln!(
-        log_file,
-        "{},\"{}\",\"{}\",\"{}\",\"{}\"",
-        info.current_version, version_log, build_date, built_by, commit_id
+    // Append the whole CSV row in one go, with the stats of the build being saved
+    let [build_ms, artifact_bytes, warnings, errors] =
+        BuildStats::log_fields(info.last_build.as_ref());
+    append_record(
+        &log_path,
+        &format_log_row(&[
+            &info.current_version,
+            version_log,
+            &build_date,
+            built_by,
+            commit_id,
+            &build_ms,
+            &artifact_bytes,
+            &warnings,
+            &errors,
+        ]),
     )?;
 
     println!("Logged: Version {} by {}", info.current_version, built_by);
     Ok(())
 }
 
-pub(crate) fn show_specific_version_logs(versions: &[String]) -> Result<(), ArkhamError> {
+// --dry-run : the row log_version would append
+pub fn plan_log_version(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    commit_id: &str,
+    plan: &mut DryRun,
+) {
+    let log_path = context.log_file();
+    if !log_path.exists() {
+        plan.create(&log_path, "schema marker & header");
+    }
+
+    let build_date = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let [build_ms, artifact_bytes, warnings, errors] =
+        BuildStats::log_fields(info.last_build.as_ref());
+    let row = format_log_row(&[
+        &info.current_version,
+        "<message>",
+        &build_date,
+        "<built by>",
+        commit_id,
+        &build_ms,
+        &artifact_bytes,
+        &warnings,
+        &errors,
+    ]);
+    plan.write(&log_path, &format!("+ {}", row));
+}
+
+pub fn show_specific_version_logs(
+    context: &ProjectContext,
+    versions: &[String],
+) -> Result<(), ArkhamError> {
     // Check if versions are specified
     if versions.is_empty() {
         return Err(ArkhamError::NoVersionSpecified);
@@ -189,40 +348,19 @@ pub(crate) fn show_specific_version_logs(versions: &[String]) -> Result<(), Arkh
         return Err(ArkhamError::MultipleVersionErrors(validation_err));
     }
 
-    if !Path::new(VERSION_LOGS_FILE).exists() {
+    let log_path = context.log_file();
+    if !log_path.exists() {
         println!("No Version logs found!\n");
         return Ok(());
     }
 
-    let log_file = File::open(VERSION_LOGS_FILE)?;
-    let reader = BufReader::new(log_file);
     let mut records = Vec::new();
     let mut validation_errors = Vec::new();
 
-    // Skip header line
-    let mut lines = reader.lines();
-    let _ = lines.next();
-
-    // Process matching version lines
-    for (line_num, line_result) in lines.enumerate() {
-        let line = line_result?;
-
+    // Process matching version lines (schema marker & header skipped)
+    for (line_num, line) in read_log_lines(&log_path)?.rows {
         // Parse CSV fields
-        let mut fields = Vec::new();
-        let mut current_field = String::new();
-        let mut in_quotes = false;
-
-        for c in line.chars() {
-            match c {
-                '"' => in_quotes = !in_quotes,
-                ',' if !in_quotes => {
-                    fields.push(current_field.trim().trim_matches('"').to_string());
-                    current_field.clear();
-                }
-                _ => current_field.push(c),
-            }
-        }
-        fields.push(current_field.trim().trim_matches('"').to_string());
+        let fields = parse_log_row(&line);
 
         // Only process if version matches
         if !versions.iter().any(|ver| ver == &fields[0]) {
@@ -230,10 +368,11 @@ pub(crate) fn show_specific_version_logs(versions: &[String]) -> Result<(), Arkh
         }
 
         // Validate fields count
-        if fields.len() != 5 {
+        if fields.len() != LOG_FIELDS {
             validation_errors.push(format!(
-                "Corrupt version info at line {}: Expected 5 fields, found {}",
-                line_num + 2,
+                "Corrupt version info at line {}: Expected {} fields, found {}",
+                line_num,
+                LOG_FIELDS,
                 fields.len()
             ));
             continue;
@@ -245,11 +384,7 @@ pub(crate) fn show_specific_version_logs(versions: &[String]) -> Result<(), Arkh
             log: fields[1].clone(),
             date: fields[2].clone(),
             builder: fields[3].clone(),
-            commit: if fields[4].len() > 8 {
-                format!("{}...", &fields[4][..8])
-            } else {
-                fields[4].clone()
-            },
+            commit: short_state_id(&fields[4]),
         });
     }
 
@@ -273,11 +408,8 @@ pub(crate) fn show_specific_version_logs(versions: &[String]) -> Result<(), Arkh
     let styled_table = table
         .with(Style::ascii())
         .with(Padding::new(1, 1, 0, 0))
-        .with(
-            Modify::new(Segment::all())
-                .with(Width::wrap(20))
-                .with(Alignment::center()),
-        );
+        .with(Modify::new(Segment::all()).with(Alignment::center()))
+        .with(fit_to_terminal());
 
     // Print table with heading
     println!("\nVersion Log History:");
@@ -286,68 +418,178 @@ pub(crate) fn show_specific_version_logs(versions: &[String]) -> Result<(), Arkh
     Ok(())
 }
 
-// Older Parser
-// fn parse_version_row(line: &str) -> Vec<String> {
-//     let mut fields = Vec::new();
-//     let mut current_field = String::new();
-//     let mut in_quotes = false;
-//
-//     for c in line.chars() {
-//         match c {
-//             '"' => in_quotes = !in_quotes,
-//             ',' if !in_quotes => {
-//                 fields.push(current_field.clone());
-//                 current_field.clear();
-//             }
-//             _ => current_field.push(c),
-//         }
-//     }
-//     fields.push(current_field);
-//     fields
-// }
-
-// Fetch All the versions
-pub(crate) fn show_version_logs() -> Result<(), ArkhamError> {
-    if !Path::new(VERSION_LOGS_FILE).exists() {
-        println!("No Version logs found!\n");
-        return Ok(());
+// One row per line : newlines & backslashes escaped, quotes doubled (CSV style)
+pub fn escape_log_field(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '"' => escaped.push_str("\"\""),
+            _ => escaped.push(c),
+        }
     }
+    escaped
+}
 
-    let log_file = File::open(VERSION_LOGS_FILE)?;
-    let reader = BufReader::new(log_file);
-    let mut records = Vec::new();
-    let mut validation_errors = Vec::new();
+// \n, \r & \\ back to what they were (quotes are undone by parse_log_row)
+pub fn unescape_log_field(field: &str) -> String {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
 
-    // Skip header line
-    let mut lines = reader.lines();
-    let _ = lines.next();
+// CSV Row Writer : version_name,"version_log","build_date","built_by","commit_id","build_ms",..
+pub fn format_log_row(fields: &[&str; LOG_FIELDS]) -> String {
+    let mut row = fields[0].to_string();
+    for field in &fields[1..] {
+        row.push_str(&format!(",\"{}\"", escape_log_field(field)));
+    }
+    row
+}
 
-    // Process each line
-    for (line_num, line_result) in lines.enumerate() {
-        let line = line_result?;
+// Split on unquoted commas, each piece untouched (quotes & escapes still in)
+pub fn split_log_row(line: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+
+    for (pos, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes, // "" inside quotes toggles twice : still quoted
+            ',' if !in_quotes => {
+                pieces.push(&line[start..pos]);
+                start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+    pieces.push(&line[start..]);
+    pieces
+}
 
-        // Parse CSV fields
-        let mut fields = Vec::new();
-        let mut current_field = String::new();
-        let mut in_quotes = false;
-
-        for c in line.chars() {
-            match c {
-                '"' => in_quotes = !in_quotes,
-                ',' if !in_quotes => {
-                    fields.push(current_field.trim().trim_matches('"').to_string());
-                    current_field.clear();
-                }
-                _ => current_field.push(c),
+// Quotes of one piece undone : "" inside quotes is a literal quote, the others go (commas kept)
+pub fn unquote_log_field(piece: &str) -> String {
+    let mut unquoted = String::with_capacity(piece.len());
+    let mut in_quotes = false;
+    let mut chars = piece.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                unquoted.push('"');
+                chars.next();
             }
+            '"' => in_quotes = !in_quotes,
+            _ => unquoted.push(c),
         }
-        fields.push(current_field.trim().trim_matches('"').to_string());
+    }
+    unquoted
+}
+
+// CSV Row Parser : version_name,"version_log","build_date","built_by","commit_id","build_ms",..
+pub fn parse_log_row(line: &str) -> Vec<String> {
+    split_log_row(line)
+        .into_iter()
+        .map(|piece| unescape_log_field(unquote_log_field(piece).trim()))
+        .collect()
+}
+
+// Fetch the (full) commit / snapshot ID logged for a version : latest entry wins
+pub fn find_commit_id(context: &ProjectContext, version: &str) -> Result<String, ArkhamError> {
+    validate_version(version)?;
+
+    let log_path = context.log_file();
+    if !log_path.exists() {
+        return Err(ArkhamError::VersionNotFound(version.to_string()));
+    }
+
+    let mut commit_id = None;
+    for (_, line) in read_log_lines(&log_path)?.rows {
+        let fields = parse_log_row(&line);
+        if fields.len() == LOG_FIELDS && fields[0] == version {
+            commit_id = Some(fields[4].clone());
+        }
+    }
+
+    match commit_id {
+        Some(id) if !id.is_empty() => Ok(id),
+        _ => Err(ArkhamError::VersionNotFound(version.to_string())),
+    }
+}
+
+// Table column : a git hash shortened to 8, snapshot IDs (usable with restore / diff) & anything else in full
+fn short_state_id(state_id: &str) -> String {
+    let is_git_hash = state_id.len() == 40 && state_id.chars().all(|c| c.is_ascii_hexdigit());
+    match is_git_hash {
+        true => format!("{}...", state_id.chars().take(8).collect::<String>()),
+        false => state_id.to_string(),
+    }
+}
+
+// One .version.log row
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct LogEntry {
+    pub version: String,
+    pub log: String,
+    pub build_date: String,
+    pub built_by: String,
+    pub commit_id: String,         // git commit or snapshot ID
+    pub stats: Option<BuildStats>, // None for versions logged before arkham kept them
+}
+
+impl LogEntry {
+    // Table row : commit ID shortened
+    fn to_record(&self) -> VersionRecord {
+        VersionRecord {
+            version: self.version.clone(),
+            log: self.log.clone(),
+            date: self.build_date.clone(),
+            builder: self.built_by.clone(),
+            commit: short_state_id(&self.commit_id),
+        }
+    }
+}
+
+// Every logged version, oldest first : any corrupt row => MultipleVersionErrors listing them all
+pub fn read_version_log(context: &ProjectContext) -> Result<Vec<LogEntry>, ArkhamError> {
+    let log_path = context.log_file();
+    if !log_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    let mut validation_errors = Vec::new();
+
+    // Process each line (schema marker & header skipped)
+    for (line_num, line) in read_log_lines(&log_path)?.rows {
+        // Parse CSV fields
+        let fields = parse_log_row(&line);
 
         // Validate fields count
-        if fields.len() != 5 {
+        if fields.len() != LOG_FIELDS {
             validation_errors.push(format!(
-                "Corrupt version info at line {}: Expected 5 fields, found {}",
-                line_num + 2, // +2 because we skipped header and 0-based index
+                "Corrupt version info at line {}: Expected {} fields, found {}",
+                line_num,
+                LOG_FIELDS,
                 fields.len()
             ));
             continue;
@@ -355,21 +597,19 @@ pub(crate) fn show_version_logs() -> Result<(), ArkhamError> {
 
         // Validate version format
         if let Err(e) = validate_version(&fields[0]) {
-            validation_errors.push(format!("Invalid version at line {}: {}", line_num + 2, e));
+            validation_errors.push(format!("Invalid version at line {}: {}", line_num, e));
             continue;
         }
 
-        // Create record
-        records.push(VersionRecord {
-            version: fields[0].clone(),
-            log: fields[1].clone(),
-            date: fields[2].clone(),
-            builder: fields[3].clone(),
-            commit: if fields[4].len() > 8 {
-                format!("{}...", &fields[4][..8])
-            } else {
-                fields[4].clone()
-            },
+        let stats = BuildStats::from_log_fields(&fields[5..]);
+        let mut fields = fields.into_iter();
+        entries.push(LogEntry {
+            version: fields.next().unwrap_or_default(),
+            log: fields.next().unwrap_or_default(),
+            build_date: fields.next().unwrap_or_default(),
+            built_by: fields.next().unwrap_or_default(),
+            commit_id: fields.next().unwrap_or_default(),
+            stats,
         });
     }
 
@@ -377,6 +617,15 @@ pub(crate) fn show_version_logs() -> Result<(), ArkhamError> {
     if !validation_errors.is_empty() {
         return Err(ArkhamError::MultipleVersionErrors(validation_errors));
     }
+    Ok(entries)
+}
+
+// Fetch All the versions
+pub fn show_version_logs(context: &ProjectContext) -> Result<(), ArkhamError> {
+    let records: Vec<VersionRecord> = read_version_log(context)?
+        .iter()
+        .map(LogEntry::to_record)
+        .collect();
 
     if records.is_empty() {
         println!("No Version logs found!\n");
@@ -389,11 +638,8 @@ pub(crate) fn show_version_logs() -> Result<(), ArkhamError> {
     let styled_table = table
         .with(Style::ascii())
         .with(Padding::new(1, 1, 0, 0))
-        .with(
-            Modify::new(Segment::all())
-                .with(Width::wrap(20))
-                .with(Alignment::center()),
-        );
+        .with(Modify::new(Segment::all()).with(Alignment::center()))
+        .with(fit_to_terminal());
 
     // Print table with spacing
     println!("\nVersion History:");
@@ -403,13 +649,14 @@ pub(crate) fn show_version_logs() -> Result<(), ArkhamError> {
 }
 
 // Fetching && Printing Current version info
-pub(crate) fn current_version_info() -> Result<(), ArkhamError> {
+pub fn current_version_info(context: &ProjectContext) -> Result<(), ArkhamError> {
     // reading the version file
-    match read_version_info()? {
+    match read_version_info(context)? {
         Some(ver_info) => {
             println!("App/Executable Name:  {}", ver_info.project_name);
             println!("App Version:          {}", ver_info.current_version);
             println!("App Root Folder:      {}", ver_info.project_root);
+            println!("App Artifacts:        {}", ver_info.artifacts.join(", "));
             Ok(())
         }
         None => Err(ArkhamError::MissingVersionInfo),
@@ -423,211 +670,819 @@ fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
         .position(|window| window == needle) // sliding window length : needle.len()
 }
 
-/*
-    Perf update : instead of fs::copy() && fs::remove() using fs::rename() as low on resources
-*/
-fn update_executable_version(info: &ProjectInfo, version: &str) -> Result<(), ArkhamError> {
-    let current_date = Local::now().format("%Y-%m-%d").to_string();
+// <artifact>_v_<version>, next to the artifact
+pub fn versioned_path(context: &ProjectContext, artifact: &str, version: &str) -> PathBuf {
+    let artifact_path = context.path(artifact);
+    let versioned_name = format!("{}_v_{}", artifact_file_name(artifact), version);
+    artifact_path.with_file_name(versioned_name)
+}
 
-    // debug_log(&format!("Current date: {}", &current_date));
+// Stamped build of version still around : next to the artifact, else moved to prev_builds/
+pub fn kept_build(context: &ProjectContext, artifact: &str, version: &str) -> Option<PathBuf> {
+    let current = versioned_path(context, artifact, version);
+    let rotated = context.prev_builds_dir().join(current.file_name()?);
+    [current, rotated].into_iter().find(|path| path.is_file())
+}
 
-    // Create prev_builds directory if it doesn't exist
-    let prev_builds_dir = "prev_builds";
-    if !Path::new(prev_builds_dir).exists() {
-        fs::create_dir(prev_builds_dir)?;
-        debug_log("Created prev_builds directory");
-    }
+pub fn artifact_file_name(artifact: &str) -> String {
+    Path::new(artifact)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or(artifact.to_string())
+}
+
+// What arkham appends to a stamped executable
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Stamp {
+    pub version: String,
+    pub build_date: String,
+}
 
-    if Path::new(&info.project_name).exists() {
-        // read the existing executable
-        let mut exec_content = fs::read(&info.project_name)?;
+const STAMP_START: &[u8] = b"--VERSION_INFO_START--";
+const STAMP_END: &[u8] = b"--VERSION_INFO_END--";
 
-        // Search pattern: remove previous version
-        let start_pattern = b"--VERSION_INFO_START--";
+// Stamp carried by an executable, None if it was never stamped
+pub fn read_stamp(executable: &Path) -> Result<Option<Stamp>, ArkhamError> {
+    let exec_content = fs::read(executable)?;
 
-        // Find the first occurrence of version info
-        if let Some(pos) = find_subsequence(&exec_content, start_pattern) {
-            // debug_log("deleting prev version..");
-            // Truncate at the start of first version info
-            exec_content.truncate(pos);
+    let start = match find_subsequence(&exec_content, STAMP_START) {
+        Some(pos) => pos + STAMP_START.len(),
+        None => return Ok(None),
+    };
+    let end = find_subsequence(&exec_content[start..], STAMP_END)
+        .map(|pos| start + pos)
+        .unwrap_or(exec_content.len());
+
+    let block = String::from_utf8_lossy(&exec_content[start..end]);
+    let mut stamp = Stamp {
+        version: String::new(),
+        build_date: String::new(),
+    };
+    for line in block.lines() {
+        if let Some(version) = line.strip_prefix("Version: ") {
+            stamp.version = version.trim().to_string();
+        } else if let Some(build_date) = line.strip_prefix("Build Date: ") {
+            stamp.build_date = build_date.trim().to_string();
         }
+    }
+    Ok(Some(stamp))
+}
 
-        // Create version info
-        let version_info = format!(
-            "\n--VERSION_INFO_START--\nVersion: {}\nBuild Date: {}\n--VERSION_INFO_END--\n",
-            version, current_date
-        )
-        .into_bytes();
-
-        // Append version info to executable
-        exec_content.extend(version_info);
-
-        // Debug : Print the last portion containing version info as a string
-        // if let Ok(version_text) = String::from_utf8(
-        //     exec_content
-        //         .clone()
-        //         .into_iter()
-        //         .skip(exec_content.len() - 100)
-        //         .collect(),
-        // ) {
-        //     debug_log("\nLast 100 bytes as text (including version info):");
-        //     debug_log(&format!("{}", version_text));
-        // }
-
-        // New executable
-        let versioned_name = format!("{}_v_{}", info.project_name, version);
-
-        // Write the updated executable
-        fs::write(&versioned_name, exec_content)?;
-
-        // Update Permissions
-        let mut perms = fs::metadata(&versioned_name)?.permissions();
-        perms.set_mode(0o777); // read-write-exec
-        fs::set_permissions(&versioned_name, perms)?;
-
-        // Verify version info
-        if !verify_version_info(&versioned_name, version)? {
-            fs::remove_file(&versioned_name)?;
-            return Err(ArkhamError::BuildError(
-                "Failed to write version info to executable!".to_string(),
+// The block write_stamp appends
+fn stamp_block(version: &str, build_date: &str) -> Vec<u8> {
+    format!(
+        "\n--VERSION_INFO_START--\nVersion: {}\nBuild Date: {}\n--VERSION_INFO_END--\n",
+        version, build_date
+    )
+    .into_bytes()
+}
+
+// Executable bytes without any stamp
+fn unstamped_len(exec_content: &[u8]) -> usize {
+    find_subsequence(exec_content, STAMP_START).unwrap_or(exec_content.len())
+}
+
+// Size write_stamp would produce, nothing written
+pub fn stamped_size(source: &Path, version: &str, build_date: &str) -> Result<u64, ArkhamError> {
+    let exec_content = fs::read(source)?;
+    Ok((unstamped_len(&exec_content) + stamp_block(version, build_date).len()) as u64)
+}
+
+// Stamp date : the day, as update_executable_version writes it
+pub fn stamp_date() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+// source + fresh stamp (any previous one dropped) => destination, executable
+pub fn write_stamp(
+    source: &Path,
+    destination: &Path,
+    version: &str,
+    build_date: &str,
+) -> Result<(), ArkhamError> {
+    // read the existing executable
+    let mut exec_content = fs::read(source)?;
+
+    // Truncate at the start of first version info (previous stamp dropped)
+    exec_content.truncate(unstamped_len(&exec_content));
+
+    // Append version info to executable
+    exec_content.extend(stamp_block(version, build_date));
+
+    // Write the updated executable
+    fs::write(destination, &exec_content)?;
+    trace_write(destination, "stamp", exec_content.len());
+
+    // Update Permissions
+    let mut perms = fs::metadata(destination)?.permissions();
+    perms.set_mode(0o777); // read-write-exec
+    fs::set_permissions(destination, perms)?;
+    Ok(())
+}
+
+// Stamped copy of one artifact, written to destination (a staging path)
+fn stamp_artifact(
+    context: &ProjectContext,
+    artifact: &str,
+    version: &str,
+    build_date: &str,
+    destination: &Path,
+) -> Result<(), ArkhamError> {
+    write_stamp(&context.path(artifact), destination, version, build_date)?;
+
+    // Verify version info
+    if !verify_version_info(destination, version)? {
+        return Err(ArkhamError::BuildError(format!(
+            "Failed to write version info to {}!",
+            artifact
+        )));
+    }
+
+    Ok(())
+}
+
+// sha256sum-compatible manifest for the whole unit, listing the final (versioned) names
+fn checksum_manifest(staged: &[(PathBuf, PathBuf)]) -> Result<String, ArkhamError> {
+    let mut manifest = String::new();
+    for (staging_path, versioned_path) in staged {
+        let digest = Sha256::digest(fs::read(staging_path)?);
+        let file_name = versioned_path.file_name().unwrap_or_default();
+        manifest.push_str(&format!("{:x}  {}\n", digest, file_name.to_string_lossy()));
+    }
+    Ok(manifest)
+}
+
+/*
+    Stage -> install -> rotate, every step recorded in the transaction
+    signing_key => each build gets its <build>.sig, installed & rotated with it
+    Returns the versioned paths & the checksum manifest path
+*/
+fn stage_and_swap(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    version: &str,
+    build_date: &str,
+    signing_key: Option<&SigningKey>,
+    transaction: &mut FsTransaction,
+) -> Result<(Vec<PathBuf>, PathBuf), ArkhamError> {
+    // Stamped copies under temp names first : nothing visible changes if one fails
+    let mut staged: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for artifact in &info.artifacts {
+        let versioned_path = versioned_path(context, artifact, version);
+        let staging_path = temp_path(&versioned_path, "new");
+        transaction.track_new(&staging_path)?;
+        stamp_artifact(context, artifact, version, build_date, &staging_path)?;
+        staged.push((staging_path, versioned_path));
+    }
+
+    let checksum_path = context.path(&format!(
+        "{}_v_{}{}",
+        info.project_name, version, CHECKSUM_EXTENSION
+    ));
+    let manifest = checksum_manifest(&staged)?;
+    let mut signatures: Vec<(PathBuf, String)> = Vec::new();
+    if let Some(key) = signing_key {
+        for (staging_path, versioned_path) in &staged {
+            signatures.push((
+                signature_path(versioned_path),
+                sign_file(key, staging_path)?,
             ));
         }
+    }
 
-        // remove old symlink
-        if Path::new(&info.project_name).exists() {
-            fs::remove_file(&info.project_name)?;
-        }
+    // Install : versioned files, checksums, symlinks, then the new version in .version.info
+    for (staging_path, versioned_path) in &staged {
+        transaction.install(staging_path, versioned_path)?;
+    }
+    transaction.write_file(&checksum_path, manifest.as_bytes())?;
+    for (signature_path, signature) in &signatures {
+        transaction.write_file(signature_path, signature.as_bytes())?;
+    }
 
-        // create new symlink
-        std::os::unix::fs::symlink(&versioned_name, &info.project_name)?;
+    // Swap symlinks : relative target, sits next to the link
+    for (artifact, (_, versioned_path)) in info.artifacts.iter().zip(&staged) {
+        let link = context.path(artifact);
+        transaction.replace_symlink(
+            &link,
+            Path::new(versioned_path.file_name().unwrap_or_default()),
+        )?;
 
         // Verify Symlink
-        if !Path::new(&info.project_name).exists() {
+        if !link.exists() {
+            return Err(ArkhamError::BuildError(format!(
+                "Failed to create Symlink for {}!",
+                artifact
+            )));
+        }
+    }
+
+    if version != info.current_version {
+        let updated_info = ProjectInfo {
+            current_version: version.to_string(),
+            ..info.clone()
+        };
+        transaction.write_file(
+            &context.info_file(),
+            version_info_contents(&updated_info).as_bytes(),
+        )?;
+    }
+
+    // move older versions to prev_builds/ : stamped copies + checksum manifests
+    let versioned_paths: Vec<PathBuf> = staged.into_iter().map(|(_, path)| path).collect();
+    let mut current_files = versioned_paths.clone();
+    current_files.push(checksum_path.clone());
+    current_files.extend(signatures.into_iter().map(|(path, _)| path));
+
+    for (from, to) in rotation_moves(context, info, &current_files)? {
+        transaction.rename(&from, &to)?;
+    }
+
+    // Verify final state
+    for (artifact, versioned_path) in info.artifacts.iter().zip(&versioned_paths) {
+        if !versioned_path.exists() || !context.path(artifact).exists() {
             return Err(ArkhamError::BuildError(
-                "Failed to create Symlink!".to_string(),
+                "Failed to verify final executable state".to_string(),
             ));
         }
+    }
+
+    Ok((versioned_paths, checksum_path))
+}
 
-        // move older versions to prev_builds/
-        let current_dir = std::env::current_dir()?;
-        for entry in fs::read_dir(&current_dir)? {
+// Older <name>_v_* builds & checksums next to the artifacts => (from, to in prev_builds/)
+fn rotation_moves(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    current_files: &[PathBuf],
+) -> Result<Vec<(PathBuf, PathBuf)>, ArkhamError> {
+    let prev_builds_dir = context.prev_builds_dir();
+    let mut rotate_prefixes: Vec<(PathBuf, String)> = info
+        .artifacts
+        .iter()
+        .map(|artifact| {
+            let dir = context.path(artifact).parent().map(Path::to_path_buf);
+            (
+                dir.unwrap_or(context.root.clone()),
+                format!("{}_v_", artifact_file_name(artifact)),
+            )
+        })
+        .collect();
+    rotate_prefixes.push((context.root.clone(), format!("{}_v_", info.project_name)));
+
+    let mut moves: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for (dir, prefix) in &rotate_prefixes {
+        for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let file_name = entry.file_name().to_string_lossy().to_string();
 
-            // version exec move
-            if file_name.starts_with(&format!("{}_v_", info.project_name))
-                && file_name != versioned_name
+            // version exec move (files set aside by a transaction stay put)
+            if file_name.starts_with(prefix)
+                && !file_name.ends_with(TEMP_FILE_SUFFIX)
+                && !current_files.contains(&entry.path())
+                && !moves.iter().any(|(from, _)| *from == entry.path())
             {
-                let dest_path = format!("{}/{}", prev_builds_dir, file_name);
-                fs::rename(file_name, dest_path)?;
+                moves.push((entry.path(), prev_builds_dir.join(&file_name)));
             }
         }
+    }
+    Ok(moves)
+}
 
-        // Keep only last 10 builds in prev_builds
-        cleanup_old_builds(prev_builds_dir, &info.project_name)?;
+/*
+    Perf update : instead of fs::copy() && fs::remove() using fs::rename() as low on resources
+    Every artifact is stamped, checksummed, symlinked & rotated as one unit
+    The bump is one transaction : any failure puts back the previous version, symlinks & files
+*/
+pub fn update_executable_version(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    version: &str,
+) -> Result<(), ArkhamError> {
+    let current_date = stamp_date();
 
-        // Verify final state
-        if !Path::new(&versioned_name).exists() || !Path::new(&info.project_name).exists() {
-            return Err(ArkhamError::BuildError(
-                "Failed to verify final executable state".to_string(),
-            ));
+    // debug_log(&format!("Current date: {}", &current_date));
+
+    // Create prev_builds directory if it doesn't exist
+    let prev_builds_dir = context.prev_builds_dir();
+    if !prev_builds_dir.exists() {
+        fs::create_dir_all(&prev_builds_dir)?;
+        debug_log("Created prev_builds directory");
+    }
+
+    // All artifacts must exist before anything gets touched
+    let missing: Vec<&str> = info
+        .artifacts
+        .iter()
+        .filter(|artifact| !context.path(artifact).exists())
+        .map(|artifact| artifact.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(ArkhamError::BuildError(format!(
+            "Executable {} not found",
+            missing.join(", ")
+        )));
+    }
+
+    // An unreadable signing key stops the bump before anything is staged
+    let signing_key = configured_signing_key(context)?;
+
+    let mut transaction = FsTransaction::new(&context.root);
+    let staged = stage_and_swap(
+        context,
+        info,
+        version,
+        &current_date,
+        signing_key.as_ref(),
+        &mut transaction,
+    );
+    let (versioned_paths, checksum_path) = match staged {
+        Ok(installed) => {
+            transaction.commit();
+            installed
         }
+        Err(e) => {
+            println!(
+                "Version update failed, rolling back to {}..",
+                info.current_version
+            );
+            transaction.rollback();
+            return Err(e);
+        }
+    };
 
+    // Keep only last 10 builds in prev_builds : after the commit, a failed bump never costs history
+    if let Err(e) = cleanup_old_builds(&prev_builds_dir, info) {
         println!(
-            "Current version = {} created, Older versions being moved to ./prev_builds/",
-            versioned_name
+            "Warning: could not prune {}: {}",
+            prev_builds_dir.display(),
+            e
         );
+    }
+
+    for (artifact, versioned_path) in info.artifacts.iter().zip(&versioned_paths) {
         println!(
             "Symlink '{}' points to '{}'",
-            info.project_name, versioned_name
+            artifact,
+            versioned_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
         );
-    } else {
-        return Err(ArkhamError::BuildError(format!(
-            "Executable {} not found",
-            info.project_name
-        )));
     }
+    println!(
+        "Current version = {} created ({} artifact(s), checksums in {}), Older versions being moved to ./prev_builds/",
+        version,
+        versioned_paths.len(),
+        checksum_path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    if let Some(key) = &signing_key {
+        println!(
+            "Signed with key {} ({} next to each build)",
+            key_id(&key.verifying_key()),
+            SIGNATURE_EXTENSION
+        );
+    }
+
+    Ok(())
+}
+
+// --dry-run : every file update_executable_version would create, rename, link or delete
+fn plan_executable_version(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    version: &str,
+    plan: &mut DryRun,
+) -> Result<(), ArkhamError> {
+    let prev_builds_dir = context.prev_builds_dir();
+    if !prev_builds_dir.exists() {
+        plan.create(&prev_builds_dir, "directory");
+    }
+
+    let mut current_files: Vec<PathBuf> = Vec::new();
+    for artifact in &info.artifacts {
+        let versioned_path = versioned_path(context, artifact, version);
+        let replaces = if versioned_path.exists() {
+            ", replaces the existing one"
+        } else {
+            ""
+        };
+        plan.create(
+            &versioned_path,
+            &format!("{} stamped with {}{}", artifact, version, replaces),
+        );
+        current_files.push(versioned_path);
+    }
+
+    let checksum_path = context.path(&format!(
+        "{}_v_{}{}",
+        info.project_name, version, CHECKSUM_EXTENSION
+    ));
+    plan.create(&checksum_path, "sha256 of every artifact");
+
+    if let Some(signing_key) = read_config(&context.root)?.signing_key {
+        for versioned_path in current_files.clone() {
+            plan.create(
+                &signature_path(&versioned_path),
+                &format!("ed25519 signature, key {}", signing_key),
+            );
+            current_files.push(signature_path(&versioned_path));
+        }
+    }
+    current_files.push(checksum_path);
 
+    for (artifact, versioned_path) in info.artifacts.iter().zip(&current_files) {
+        plan.symlink(
+            &context.path(artifact),
+            Path::new(versioned_path.file_name().unwrap_or_default()),
+        );
+    }
+
+    if version != info.current_version {
+        plan.write(
+            &context.info_file(),
+            &format!("current_version {} -> {}", info.current_version, version),
+        );
+    }
+    plan.write(
+        &context.info_file(),
+        "last_build_* : time, size, warning counts & fingerprint of this build",
+    );
+    plan.write(
+        &context.stats_file(),
+        &format!(
+            "{} row : time, size & warning counts of this build",
+            version
+        ),
+    );
+
+    let moves = rotation_moves(context, info, &current_files)?;
+    for (from, to) in &moves {
+        plan.rename(from, to);
+    }
+
+    let incoming: Vec<PathBuf> = moves.into_iter().map(|(from, _)| from).collect();
+    for old_file in old_builds(&prev_builds_dir, info, &incoming)? {
+        plan.delete(
+            &old_file,
+            &format!("only the last {} builds are kept", MAX_PREV_BUILDS),
+        );
+    }
     Ok(())
 }
 
 // just for verification
-fn verify_version_info(executable: &str, version: &str) -> Result<bool, ArkhamError> {
-    let output = Command::new("strings").arg(executable).output()?;
+pub fn verify_version_info(executable: &Path, version: &str) -> Result<bool, ArkhamError> {
+    Ok(read_stamp(executable)?.is_some_and(|stamp| stamp.version == version))
+}
 
-    let content = String::from_utf8_lossy(&output.stdout);
-    Ok(content.contains(&format!("Version: {}", version)))
+// Keep the last MAX_PREV_BUILDS builds of every artifact (+ their checksum manifests)
+fn cleanup_old_builds(prev_builds_dir: &Path, info: &ProjectInfo) -> Result<(), ArkhamError> {
+    for old_file in old_builds(prev_builds_dir, info, &[])? {
+        fs::remove_file(&old_file)?;
+        trace_event("remove", &[("file", old_file.display().to_string())]);
+    }
+    Ok(())
 }
 
-fn cleanup_old_builds(prev_builds_dir: &str, base_name: &str) -> Result<(), ArkhamError> {
-    let mut builds: Vec<(String, fs::Metadata)> = Vec::new();
+// What cleanup_old_builds deletes, incoming = files about to be moved into prev_builds/
+fn old_builds(
+    prev_builds_dir: &Path,
+    info: &ProjectInfo,
+    incoming: &[PathBuf],
+) -> Result<Vec<PathBuf>, ArkhamError> {
+    let mut old_files = Vec::new();
+    for artifact in &info.artifacts {
+        old_files.extend(builds_past_limit(
+            prev_builds_dir,
+            &format!("{}_v_", artifact_file_name(artifact)),
+            None,
+            incoming,
+        )?);
+    }
+    old_files.extend(builds_past_limit(
+        prev_builds_dir,
+        &format!("{}_v_", info.project_name),
+        Some(CHECKSUM_EXTENSION),
+        incoming,
+    )?);
+    Ok(old_files)
+}
+
+// suffix None => builds themselves (sidecar files skipped), Some(ext) => only that sidecar kind
+fn builds_past_limit(
+    prev_builds_dir: &Path,
+    prefix: &str,
+    suffix: Option<&str>,
+    incoming: &[PathBuf],
+) -> Result<Vec<PathBuf>, ArkhamError> {
+    let mut candidates: Vec<PathBuf> = incoming.to_vec();
+    if prev_builds_dir.is_dir() {
+        for entry in fs::read_dir(prev_builds_dir)? {
+            candidates.push(entry?.path());
+        }
+    }
 
-    for entry in fs::read_dir(prev_builds_dir)? {
-        let entry = entry?;
-        let file_name = entry.file_name().to_string_lossy().to_string();
+    let mut builds: Vec<(String, fs::Metadata)> = Vec::new();
+    for path in candidates {
+        let file_name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        let is_sidecar = BUILD_SIDECAR_EXTENSIONS
+            .iter()
+            .any(|ext| file_name.ends_with(ext));
+        let wanted = match suffix {
+            Some(ext) => file_name.ends_with(ext),
+            None => !is_sidecar,
+        };
 
-        if file_name.starts_with(&format!("{}_", base_name)) && !file_name.ends_with(".version") {
-            builds.push((file_name, entry.metadata()?));
+        if file_name.starts_with(prefix) && wanted {
+            builds.push((file_name, fs::symlink_metadata(&path)?));
         }
     }
 
     // Sort = newest first
-    builds.sort_by(|a, b| b.1.modified().unwrap().cmp(&a.1.modified().unwrap()));
+    builds.sort_by_key(|b| std::cmp::Reverse(b.1.modified().unwrap()));
 
     // (keep only 10 most recent)
-    for (file_name, _) in builds.iter().skip(10) {
-        let exec_path = format!("{}/{}", prev_builds_dir, file_name);
-        let version_path = format!("{}/{}.version", prev_builds_dir, file_name);
-
-        if Path::new(&exec_path).exists() {
-            fs::remove_file(&exec_path)?;
-        }
-        if Path::new(&version_path).exists() {
-            fs::remove_file(&version_path)?;
+    let mut old_files = Vec::new();
+    for (file_name, _) in builds.iter().skip(MAX_PREV_BUILDS) {
+        let exec_path = prev_builds_dir.join(file_name);
+        let version_path = prev_builds_dir.join(format!("{}.version", file_name));
+        let signature_path = prev_builds_dir.join(format!("{}{}", file_name, SIGNATURE_EXTENSION));
+
+        for old_file in [exec_path, version_path, signature_path] {
+            let arriving = incoming
+                .iter()
+                .any(|path| path.file_name() == old_file.file_name());
+            if old_file.exists() || arriving {
+                old_files.push(old_file);
+            }
         }
     }
-
-    Ok(())
+    Ok(old_files)
 }
 
 // Build & Clean
-pub(crate) fn build_project(args: &[String]) -> Result<bool, ArkhamError> {
+fn build_command(context: &ProjectContext, build_system: BuildSystem, args: &[String]) -> Command {
+    // Workspace component : its own target first, then the user's args
+    let mut build_args: Vec<String> = match context.build_target() {
+        Some(target) => build_system.target_args(target),
+        None => Vec::new(),
+    };
+    build_args.extend_from_slice(args);
+
+    // Make Command : always from the project root
+    let mut make_cmd = build_system.build_command(&build_args); // pass extra args
+    make_cmd.current_dir(&context.root);
+    make_cmd
+}
+
+// What one run of the build system did
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct BuildReport {
+    pub success: bool,
+    pub duration: Duration,
+    pub warnings: u64,
+    pub errors: u64,
+}
+
+fn is_error_line(line: &str) -> bool {
+    line.contains("error:")
+        || line.contains("Error ")
+        || (line.contains("make:***") && !line.contains("is up to date"))
+}
+
+fn is_warning_line(line: &str) -> bool {
+    line.to_lowercase().contains("warning:")
+}
+
+pub fn build_project(
+    context: &ProjectContext,
+    build_system: BuildSystem,
+    args: &[String],
+) -> Result<bool, ArkhamError> {
+    build_project_report(context, build_system, args).map(|report| report.success)
+}
+
+/*
+    One run of the build system => its report & error lines
+    failed : a non-zero exit (whatever got printed) or any error line (gcc / clang print theirs on stderr)
+*/
+fn build_report(build_output: &Output, duration: Duration) -> (BuildReport, Vec<String>) {
+    let mut report = BuildReport {
+        success: false,
+        duration,
+        warnings: 0,
+        errors: 0,
+    };
+
+    let mut error_lines = Vec::new();
+    for output in [&build_output.stdout, &build_output.stderr] {
+        for line in String::from_utf8_lossy(output).lines() {
+            if is_error_line(line) {
+                error_lines.push(line.to_string());
+                report.errors += 1;
+            } else if is_warning_line(line) {
+                report.warnings += 1;
+            }
+        }
+    }
+    report.success = build_output.status.success() && report.errors == 0;
+    (report, error_lines)
+}
+
+// build_project, plus how long it took & how many warnings / errors the compiler printed
+pub fn build_project_report(
+    context: &ProjectContext,
+    build_system: BuildSystem,
+    args: &[String],
+) -> Result<BuildReport, ArkhamError> {
     display_header_msg("Building Project!");
 
-    // Make Command
-    let mut make_cmd = Command::new("make");
-    make_cmd.args(args); // pass extra args
+    let mut make_cmd = build_command(context, build_system, args);
 
     // make output
-    let build_output = make_cmd.output()?;
-    let mut success_build = true;
+    let started = Instant::now();
+    let build_output = run_output(&mut make_cmd)?;
+    let (report, _) = build_report(&build_output, started.elapsed());
 
-    // Display Output
+    // Display Output : stdout as is, errors highlighted
     let stdout = String::from_utf8_lossy(&build_output.stdout);
+    let show_error = |line: &str| {
+        println!();
+        insert_separator();
+        println!("{}", paint(line, Tone::Error));
+        insert_separator();
+    };
     for line in stdout.lines() {
-        if line.contains("error:")
-            || line.contains("Error ")
-            || (line.contains("make:***") && !line.contains("is up to date"))
-        {
-            println!();
-            insert_separator();
-            println!("{}", line);
-            insert_separator();
-            success_build = false;
+        if is_error_line(line) {
+            show_error(line);
         } else {
             println!("{}", line);
         }
     }
+    // stderr : only its errors, the reason a build failed (warnings are counted in the report)
+    let stderr = String::from_utf8_lossy(&build_output.stderr);
+    stderr
+        .lines()
+        .filter(|line| is_error_line(line))
+        .for_each(show_error);
+    if !build_output.status.success() {
+        println!(
+            "{}",
+            paint(
+                &format!(
+                    "{} exited with {}",
+                    build_system.name(),
+                    build_output.status
+                ),
+                Tone::Error
+            )
+        );
+    }
+
+    Ok(report)
+}
+
+// The same build with its output held back : the report & the error lines (arkham watch summarizes)
+pub fn build_project_silent(
+    context: &ProjectContext,
+    build_system: BuildSystem,
+    args: &[String],
+) -> Result<(BuildReport, Vec<String>), ArkhamError> {
+    let started = Instant::now();
+    let build_output = run_output(&mut build_command(context, build_system, args))?;
+    Ok(build_report(&build_output, started.elapsed()))
+}
+
+// Size of every stamped artifact of version, summed
+fn artifact_bytes(context: &ProjectContext, info: &ProjectInfo, version: &str) -> u64 {
+    info.artifacts
+        .iter()
+        .filter_map(|artifact| fs::metadata(versioned_path(context, artifact, version)).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+// One .version.stats row : the latest build of a version, backed up or not
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltVersion {
+    pub version: String,
+    pub build_date: String,
+    pub stats: BuildStats,
+}
+
+// Every built version, first built first : rows that don't parse are skipped
+pub fn read_build_stats(context: &ProjectContext) -> Result<Vec<BuiltVersion>, ArkhamError> {
+    let stats_path = context.stats_file();
+    if !stats_path.exists() {
+        return Ok(Vec::new());
+    }
 
-    Ok(success_build)
+    let mut built = Vec::new();
+    for line in fs::read_to_string(&stats_path)?.lines().skip(1) {
+        let fields: Vec<String> = line
+            .split(',')
+            .map(|field| field.trim().to_string())
+            .collect();
+        if fields.len() != 6 || validate_version(&fields[0]).is_err() {
+            continue;
+        }
+        if let Some(stats) = BuildStats::from_log_fields(&fields[2..]) {
+            built.push(BuiltVersion {
+                version: fields[0].clone(),
+                build_date: fields[1].clone(),
+                stats,
+            });
+        }
+    }
+    trace_read(&stats_path);
+    Ok(built)
+}
+
+// version's row replaced (a rebuild of the same version) or appended, the file swapped in whole
+fn record_version_stats(
+    context: &ProjectContext,
+    version: &str,
+    stats: BuildStats,
+) -> Result<(), ArkhamError> {
+    let mut built = read_build_stats(context)?;
+    let row = BuiltVersion {
+        version: version.to_string(),
+        build_date: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        stats,
+    };
+    match built.iter_mut().find(|seen| seen.version == version) {
+        Some(seen) => *seen = row,
+        None => built.push(row),
+    }
+
+    let mut contents = format!("{}\n", BUILD_STATS_HEADER);
+    for row in &built {
+        contents.push_str(&format!(
+            "{},{},{}\n",
+            row.version,
+            row.build_date,
+            BuildStats::log_fields(Some(&row.stats)).join(",")
+        ));
+    }
+    write_atomic(&context.stats_file(), contents.as_bytes())
 }
 
-pub(crate) fn clean_project() -> Result<(), ArkhamError> {
+/*
+    Keep this build's stats & fingerprint in .version.info : the next backup logs the stats,
+    the next build compares the fingerprint (taken now, build outputs in place)
+    The stats also go to .version.stats right away : a version bumped again before a backup keeps them
+*/
+fn record_build(
+    context: &ProjectContext,
+    report: &BuildReport,
+    build_system: BuildSystem,
+    args: &[String],
+) -> Result<BuildStats, ArkhamError> {
+    let info = read_version_info(context)?.ok_or(ArkhamError::MissingVersionInfo)?;
+    let stats = BuildStats {
+        duration_ms: report.duration.as_millis() as u64,
+        artifact_bytes: artifact_bytes(context, &info, &info.current_version),
+        warnings: report.warnings,
+        errors: report.errors,
+    };
+    record_version_stats(context, &info.current_version, stats)?;
+    let fingerprint = build_fingerprint(context, &info, build_system, args)?;
+    write_version_info(
+        context,
+        &ProjectInfo {
+            last_build: Some(stats),
+            build_fingerprint: Some(fingerprint),
+            ..info
+        },
+    )?;
+    Ok(stats)
+}
+
+// arkham clean --dry-run
+pub fn plan_clean(context: &ProjectContext) -> Result<(), ArkhamError> {
+    let build_system = resolve_build_system(&context.root)?;
+    let mut plan = DryRun::new(context);
+    plan.run(build_system.clean_command().current_dir(&context.root));
+    plan.print("arkham clean");
+    Ok(())
+}
+
+pub fn clean_project(context: &ProjectContext) -> Result<(), ArkhamError> {
     display_header_msg("Cleaning Project Files!");
     std::thread::sleep(std::time::Duration::from_secs(1));
 
+    // Build system comes from .arkham.conf (detected / make if not configured)
+    let build_system = resolve_build_system(&context.root)?;
+
     // Clean CMD
-    let clean_output = Command::new("make").arg("clean").output()?;
+    let clean_output = run_output(build_system.clean_command().current_dir(&context.root))?;
 
     // clean cmd output
     println!("{}", String::from_utf8_lossy(&clean_output.stdout));
@@ -639,10 +1494,99 @@ pub(crate) fn clean_project() -> Result<(), ArkhamError> {
     Ok(())
 }
 
+// Bump? None => keep the current version
+fn ask_new_version(info: &ProjectInfo) -> Result<Option<String>, ArkhamError> {
+    print!("Do you want to update the version? (yes [y] | no [n]) = ");
+    io::stdout().flush()?;
+    let mut update_choice = String::new();
+    io::stdin().read_line(&mut update_choice)?;
+
+    if update_choice.trim().eq_ignore_ascii_case("yes")
+        || update_choice.trim().eq_ignore_ascii_case("y")
+    {
+        // Get update type
+        print!("Is this a Major or Minor Update? (MAJOR [1] | MINOR [0]) = ");
+        io::stdout().flush()?;
+
+        let mut update_type = String::new();
+        io::stdin().read_line(&mut update_type)?;
+
+        // Calculate new version
+        update_version(&info.current_version, &update_type).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+// arkham build --dry-run : the build command & everything the bump would touch, nothing run
+pub fn plan_build(
+    context: &ProjectContext,
+    args: &[String],
+    force: bool,
+) -> Result<(), ArkhamError> {
+    let info = match read_version_info(context)? {
+        Some(info) => info,
+        None => {
+            println!(
+                "No {} found: a real build would run the interactive setup (arkham init) first.",
+                context.info_file().display()
+            );
+            return Ok(());
+        }
+    };
+
+    let mut plan = DryRun::new(context);
+    let build_system = resolve_build_system(&context.root)?;
+    if !force
+        && is_up_to_date(
+            context,
+            &info,
+            &build_fingerprint(context, &info, build_system, args)?,
+        )
+    {
+        plan.note(&format!(
+            "{} {} is up to date, nothing would run (--force rebuilds anyway)",
+            info.project_name, info.current_version
+        ));
+        plan.print(&format!("arkham build ({})", info.project_name));
+        return Ok(());
+    }
+
+    let build_env = HookEnv::at(&info.current_version);
+    plan_hooks(context, &info, Hook::PreBuild, &build_env, &mut plan)?;
+    plan.run(&build_command(context, build_system, args));
+
+    // A real build asks about the bump here : no prompt in a dry run, planned as a MINOR bump
+    let minor_version = update_version(&info.current_version, "minor")?;
+    let major_version = update_version(&info.current_version, "major")?;
+    plan.note("if the build succeeds:");
+    plan_hooks(context, &info, Hook::PostBuild, &build_env, &mut plan)?;
+    plan_size_gate(&read_config(&context.root)?, &mut plan);
+    plan.note(&format!(
+        "asks for the bump: would bump MINOR -> {} (shown below), MAJOR -> {}, or keep {}",
+        minor_version, major_version, info.current_version
+    ));
+
+    let bump_env = HookEnv {
+        new_version: minor_version.clone(),
+        ..HookEnv::at(&info.current_version)
+    };
+    plan_hooks(context, &info, Hook::PreBump, &bump_env, &mut plan)?;
+    plan_executable_version(context, &info, &minor_version, &mut plan)?;
+    plan_hooks(context, &info, Hook::PostBump, &bump_env, &mut plan)?;
+
+    plan.print(&format!("arkham build ({})", info.project_name));
+    Ok(())
+}
+
 // build & update
-pub(crate) fn build_and_update(args: &[String]) -> Result<(), ArkhamError> {
+pub fn build_and_update(
+    context: &ProjectContext,
+    args: &[String],
+    force: bool,
+) -> Result<(), ArkhamError> {
     // Fetch / Create app version
-    let info = match read_version_info()? {
+    let (context, info) = match read_version_info(context)? {
         Some(info) => {
             println!("Found Existing Version(s) - fetching..");
             std::thread::sleep(std::time::Duration::from_secs(1));
@@ -650,103 +1594,304 @@ pub(crate) fn build_and_update(args: &[String]) -> Result<(), ArkhamError> {
                 "Project Name: {}\nCurrent Version: {}",
                 info.project_name, info.current_version
             ));
-            info
+            (context.clone(), info)
         }
         None => {
-            // Take info from User
-            println!("No Versioning Found! Please enter details manually: ");
-            print!("Enter Executable name (Eg. CookieUFS): ");
-            io::stdout().flush()?; // print! doesn't flush output buffer automatically
-            let mut project_name = String::new();
-            io::stdin().read_line(&mut project_name)?;
-
-            print!("Enter the current version (Eg. 3.53): ");
-            io::stdout().flush()?; // print! doesn't flush output buffer automatically
-            let mut project_version = String::new();
-            io::stdin().read_line(&mut project_version)?;
-
-            // Creating New Project Info
-            let info = ProjectInfo {
-                project_name: project_name.trim().to_string(),
-                current_version: project_version.trim().to_string(),
-                project_root: std::env::current_dir()?.to_string_lossy().to_string(),
-            };
-
-            write_version_info(
-                &info.project_name,
-                &info.current_version,
-                &info.project_root,
-            )?;
-            info
+            // First run : interactive setup, project root = nearest dir with a build file
+            println!("No Versioning Found! Setting up the project..");
+            if context.component.is_some() {
+                let info = init_project(InitOptions {
+                    context: Some(context.clone()),
+                    ..InitOptions::default()
+                })?;
+                (context.clone(), info)
+            } else {
+                let info = init_project(InitOptions::default())?;
+                (ProjectContext::at(Path::new(&info.project_root)), info)
+            }
         }
     };
 
-    if build_project(args)? {
-        display_header_msg(&format!("{}: Got built successfully!", info.project_name));
+    // Same sources & args as the last stamped build : nothing to do
+    let build_system = resolve_build_system(&context.root)?;
+    if !force {
+        let fingerprint = build_fingerprint(&context, &info, build_system, args)?;
+        if is_up_to_date(&context, &info, &fingerprint) {
+            println!(
+                "{} {} is up to date: nothing changed since the last build (--force rebuilds anyway)",
+                info.project_name, info.current_version
+            );
+            return Ok(());
+        }
+    }
+
+    // pre_build : a failing hook stops us before the build system runs
+    let build_env = HookEnv::at(&info.current_version);
+    run_hooks(&context, &info, Hook::PreBuild, &build_env)?;
 
-        print!("Do you want to update the version? (yes [y] | no [n]) = ");
-        io::stdout().flush()?;
-        let mut update_choice = String::new();
-        io::stdin().read_line(&mut update_choice)?;
-
-        if update_choice.trim().eq_ignore_ascii_case("yes")
-            || update_choice.trim().eq_ignore_ascii_case("y")
-        {
-            // Get update type
-            print!("Is this a Major or Minor Update? (MAJOR [1] | MINOR [0]) = ");
-            io::stdout().flush()?;
-
-            let mut update_type = String::new();
-            io::stdin().read_line(&mut update_type)?;
-
-            // Calculate new version
-            let new_version = match update_type.trim().to_lowercase().as_str() {
-                "major" | "1" => {
-                    let parts: Vec<&str> = info.current_version.split('.').collect();
-                    if parts.len() != 2 {
-                        return Err(ArkhamError::InvalidVersion(info.current_version));
-                    }
-                    let major: i32 = parts[0]
-                        .parse()
-                        .map_err(|_| ArkhamError::InvalidVersion(info.current_version.clone()))?;
-                    format!("{}.00", major + 1)
-                }
-                "minor" | "0" => {
-                    let parts: Vec<&str> = info.current_version.split('.').collect();
-                    if parts.len() != 2 {
-                        return Err(ArkhamError::InvalidVersion(info.current_version));
-                    }
-                    let major: i32 = parts[0]
-                        .parse()
-                        .map_err(|_| ArkhamError::InvalidVersion(info.current_version.clone()))?;
-                    let minor: i32 = parts[1]
-                        .parse()
-                        .map_err(|_| ArkhamError::InvalidVersion(info.current_version.clone()))?;
-                    format!("{}.{:02}", major, minor + 1)
-                }
-                _ => {
-                    return Err(ArkhamError::InvalidVersion(
-                        "Invalid update type".to_string(),
-                    ))
-                }
+    let report = build_project_report(&context, build_system, args)?;
+    if report.success {
+        display_success_msg(&format!("{}: Got built successfully!", info.project_name));
+        println!(
+            "Build took {}, {} warning(s)",
+            format_duration(report.duration.as_millis() as u64),
+            report.warnings
+        );
+        ensure_artifacts_built(&context, &info)?;
+        run_hooks(&context, &info, Hook::PostBuild, &build_env)?; // tests etc. : failing => no bump
+
+        let new_version = ask_new_version(&info)?;
+
+        // Size gate : before anything gets stamped, over a limit with size_gate=fail => no bump
+        let config = read_config(&context.root)?;
+        let stamped_as = new_version.as_deref().unwrap_or(&info.current_version);
+        let size = check_size(&context, &info, stamped_as, &config)?;
+        enforce_size_gate(&size, &config)?;
+
+        if let Some(new_version) = new_version {
+            let bump_env = HookEnv {
+                new_version: new_version.clone(),
+                ..HookEnv::at(&info.current_version)
             };
+            run_hooks(&context, &info, Hook::PreBump, &bump_env)?;
 
-            // Update version info
-            write_version_info(&info.project_name, &new_version, &info.project_root)?;
-            update_executable_version(&info, &new_version)?;
+            // Stamp, symlink & .version.info move to the new version together (or not at all)
+            update_executable_version(&context, &info, &new_version)?;
 
-            display_header_msg(&format!(
-                "Version Successfully Updated from {} to {}",
-                info.current_version, new_version
+            record_build(&context, &report, build_system, args)?;
+
+            display_success_msg(&format!(
+                "Version Successfully Updated from {} to {}\n{}",
+                info.current_version,
+                new_version,
+                size.summary()
             ));
+            run_hooks(&context, &info, Hook::PostBump, &bump_env)?;
         } else {
-            display_header_msg(&format!("Version unchanged: {}", info.current_version));
-            update_executable_version(&info, &info.current_version)?;
+            display_header_msg(&format!(
+                "Version unchanged: {}\n{}",
+                info.current_version,
+                size.summary()
+            ));
+            update_executable_version(&context, &info, &info.current_version)?;
+            record_build(&context, &report, build_system, args)?;
         }
     } else {
-        display_header_msg("Build failed! Check the log file");
+        display_error_msg("Build failed! Check the log file");
         return Err(ArkhamError::BuildError("Build failed".to_string()));
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    fn output(exit_code: i32, stdout: &str, stderr: &str) -> Output {
+        Output {
+            status: std::process::ExitStatus::from_raw(exit_code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn compiler_errors_on_stderr_fail_the_build() {
+        let (report, error_lines) = build_report(
+            &output(
+                2,
+                "gcc -Wall -o main main.c\n",
+                "main.c:3:1: error: expected declaration\nmake: *** [Makefile:2: main] Error 1\n",
+            ),
+            Duration::ZERO,
+        );
+        assert!(!report.success);
+        assert_eq!(report.errors, 2);
+        assert_eq!(error_lines.len(), 2);
+    }
+
+    #[test]
+    fn failed_exit_without_error_lines_fails_the_build() {
+        let (report, error_lines) = build_report(&output(1, "", "killed\n"), Duration::ZERO);
+        assert!(!report.success);
+        assert!(error_lines.is_empty());
+    }
+
+    #[test]
+    fn clean_exit_with_warnings_passes() {
+        let (report, _) = build_report(
+            &output(0, "", "main.c:5:9: warning: unused variable 'x'\n"),
+            Duration::ZERO,
+        );
+        assert!(report.success);
+        assert_eq!((report.warnings, report.errors), (1, 0));
+    }
+
+    #[test]
+    fn log_row_round_trips_through_escaping() {
+        let log = "fixed \"a, b\"\nthen c:\\tmp\\n\r";
+        let fields = [
+            "3.10",
+            log,
+            "2026-10-18 21:50:01",
+            "me",
+            "fc08a789",
+            "36",
+            "31962",
+            "0",
+            "0",
+        ];
+        let row = format_log_row(&fields);
+        assert!(!row.contains('\n'));
+        assert_eq!(parse_log_row(&row), fields);
+    }
+
+    #[test]
+    fn log_row_parses_quoted_commas_and_doubled_quotes() {
+        let fields = parse_log_row(r#"3.10,"say ""hi"", then go", "2026-10-18 21:50:01",me"#);
+        assert_eq!(
+            fields,
+            ["3.10", "say \"hi\", then go", "2026-10-18 21:50:01", "me"]
+        );
+    }
+
+    #[test]
+    fn unquoted_log_row_still_parses() {
+        assert_eq!(parse_log_row("3.10,first build"), ["3.10", "first build"]);
+        assert_eq!(parse_log_row("3.10,,x"), ["3.10", "", "x"]);
+    }
+
+    #[test]
+    fn archives_reads_a_schema_1_log_without_touching_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = ProjectContext::at(dir.path());
+        let log = "version_name,version_log,build_date,built_by,commit_id\n\
+                   3.10,\"first build\",\"2026-10-18 21:50:01\",\"me\",\"fc08a789\"\n\
+                   3.11,\"moved C:\\tmp\",\"2026-10-18 22:00:00\",\"me\",\"0a1b2c3d\"\n";
+        fs::write(context.log_file(), log).unwrap();
+
+        assert!(show_version_logs(&context).is_ok());
+        let entries = read_version_log(&context).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].log, "moved C:\\tmp");
+        assert_eq!(entries[1].stats, None);
+        assert_eq!(find_commit_id(&context, "3.10").unwrap(), "fc08a789");
+        assert_eq!(fs::read_to_string(context.log_file()).unwrap(), log);
+    }
+
+    // A fresh build of every artifact : the build system replaces the symlinks with plain files
+    fn fresh_build(context: &ProjectContext, info: &ProjectInfo, contents: &str) {
+        for artifact in &info.artifacts {
+            let path = context.path(artifact);
+            let _ = fs::remove_file(&path);
+            fs::write(&path, contents).unwrap();
+        }
+    }
+
+    #[test]
+    fn every_artifact_is_stamped_linked_and_rotated_together() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = ProjectContext::at(dir.path());
+        fs::create_dir_all(context.path("boot")).unwrap();
+        let mut info = ProjectInfo::new(
+            "main".to_string(),
+            "1.00".to_string(),
+            dir.path().to_string_lossy().to_string(),
+        );
+        info.artifacts = vec!["main".to_string(), "boot/boot.img".to_string()];
+        write_version_info(&context, &info).unwrap();
+
+        fresh_build(&context, &info, "first");
+        update_executable_version(&context, &info, "1.01").unwrap();
+
+        for (link, stamped) in [
+            ("main", "main_v_1.01"),
+            ("boot/boot.img", "boot.img_v_1.01"),
+        ] {
+            assert_eq!(
+                fs::read_link(context.path(link)).unwrap(),
+                Path::new(stamped)
+            );
+            let stamp = read_stamp(&context.path(link)).unwrap().unwrap();
+            assert_eq!(stamp.version, "1.01");
+        }
+        let manifest = fs::read_to_string(context.path("main_v_1.01.sha256")).unwrap();
+        assert!(manifest.contains("main_v_1.01") && manifest.contains("boot.img_v_1.01"));
+        let info = read_version_info(&context).unwrap().unwrap();
+        assert_eq!(info.current_version, "1.01");
+
+        fresh_build(&context, &info, "second");
+        update_executable_version(&context, &info, "1.02").unwrap();
+
+        let prev_builds = context.prev_builds_dir();
+        for rotated in ["main_v_1.01", "boot.img_v_1.01", "main_v_1.01.sha256"] {
+            assert!(
+                prev_builds.join(rotated).is_file(),
+                "{} not rotated",
+                rotated
+            );
+        }
+        assert!(!context.path("boot/boot.img_v_1.01").exists());
+        assert!(context.path("boot/boot.img_v_1.02").is_file());
+        assert_eq!(
+            kept_build(&context, "boot/boot.img", "1.01"),
+            Some(prev_builds.join("boot.img_v_1.01"))
+        );
+    }
+
+    #[test]
+    fn failed_bump_leaves_the_previous_version_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = ProjectContext::at(dir.path());
+        let mut info = ProjectInfo::new(
+            "main".to_string(),
+            "1.00".to_string(),
+            dir.path().to_string_lossy().to_string(),
+        );
+        info.artifacts = vec!["main".to_string(), "boot.img".to_string()];
+        write_version_info(&context, &info).unwrap();
+        fs::write(context.path("main"), "build").unwrap();
+        // main gets staged, then boot.img can't be read : the bump fails halfway
+        fs::create_dir(context.path("boot.img")).unwrap();
+
+        assert!(update_executable_version(&context, &info, "1.01").is_err());
+
+        let mut left: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, [".version.info", "boot.img", "main", "prev_builds"]);
+        assert!(!context.path("main").is_symlink());
+        let info = read_version_info(&context).unwrap().unwrap();
+        assert_eq!(info.current_version, "1.00");
+    }
+
+    #[test]
+    fn a_rebuild_replaces_its_version_stats_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = ProjectContext::at(dir.path());
+        let stats = |artifact_bytes| BuildStats {
+            duration_ms: 120,
+            artifact_bytes,
+            warnings: 2,
+            errors: 0,
+        };
+
+        record_version_stats(&context, "1.00", stats(1000)).unwrap();
+        record_version_stats(&context, "1.01", stats(1100)).unwrap();
+        record_version_stats(&context, "1.00", stats(900)).unwrap();
+
+        let built = read_build_stats(&context).unwrap();
+        let rows: Vec<(&str, u64)> = built
+            .iter()
+            .map(|row| (row.version.as_str(), row.stats.artifact_bytes))
+            .collect();
+        assert_eq!(rows, [("1.00", 900), ("1.01", 1100)]);
+        assert!(fs::read_to_string(context.stats_file())
+            .unwrap()
+            .starts_with(BUILD_STATS_HEADER));
+    }
+}
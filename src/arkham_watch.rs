@@ -0,0 +1,365 @@
+/*
+ WATCH MODE : arkham watch, rebuild on every save
+    the project tree is watched through inotify (notify crate), ignore lists, prev_builds/,
+    build logs & artifacts filtered out, a burst of saves debounced into one build
+    builds only : nothing stamped, no version bump, no state file written
+    each build holds the project lock : it writes into the tree an arkham build / restore works on
+*/
+use crate::arkham_config::{resolve_build_system, BuildSystem};
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_fingerprint::{build_fingerprint, is_artifact};
+use crate::arkham_lock::acquire_lock;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_render::{paint, Tone};
+use crate::arkham_snapshot::IgnoreList;
+use crate::arkham_stats::format_duration;
+use crate::arkham_trace::trace_event;
+use crate::arkham_version::{build_project_silent, read_version_info, ProjectInfo};
+use crate::arkham_workspace::tree_artifacts;
+
+use chrono::Local;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+// Error lines shown under a failed cycle, the rest counted
+const MAX_ERROR_LINES: usize = 5;
+
+// vim swap files & backups, emacs lock files : saved around the real file, not sources
+fn is_editor_noise(rel_path: &Path) -> bool {
+    let file_name = rel_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    file_name.ends_with('~')
+        || file_name.ends_with(".swp")
+        || file_name.ends_with(".swx")
+        || file_name.starts_with(".#")
+        || file_name == "4913"
+}
+
+// Paths of a change worth a build, relative to the root
+fn relevant_paths(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    ignore: &IgnoreList,
+    event: Event,
+) -> Vec<PathBuf> {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return Vec::new();
+    }
+    event
+        .paths
+        .into_iter()
+        .filter_map(|path| path.strip_prefix(&context.root).ok().map(Path::to_path_buf))
+        .filter(|rel_path| {
+            !rel_path.as_os_str().is_empty()
+                && !ignore.is_ignored(rel_path)
+                && !is_artifact(info, rel_path)
+                && !is_editor_noise(rel_path)
+        })
+        .collect()
+}
+
+// Blocks until something relevant changes, then until debounce passes without another change
+// None => the watcher is gone
+fn next_changes(
+    events: &Receiver<notify::Result<Event>>,
+    debounce: Duration,
+    relevant: impl Fn(Event) -> Vec<PathBuf>,
+) -> Option<Vec<PathBuf>> {
+    let mut changed: Vec<PathBuf> = Vec::new();
+    loop {
+        let event = if changed.is_empty() {
+            events.recv().ok()?
+        } else {
+            match events.recv_timeout(debounce) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => return Some(changed),
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        };
+        // an inotify error (queue overflow ...) : rebuild rather than miss a change
+        let paths = match event {
+            Ok(event) => relevant(event),
+            Err(_) => vec![PathBuf::from("(events lost)")],
+        };
+        for path in paths {
+            if !changed.contains(&path) {
+                changed.push(path);
+            }
+        }
+    }
+}
+
+// "main.c, util.c (+3 more)"
+fn changed_label(changed: &[PathBuf]) -> String {
+    let names: Vec<String> = changed
+        .iter()
+        .take(2)
+        .map(|path| path.display().to_string())
+        .collect();
+    match changed.len() {
+        n if n > 2 => format!("{} (+{} more)", names.join(", "), n - 2),
+        _ => names.join(", "),
+    }
+}
+
+// One build & its one line summary ; errors under it when it fails
+fn build_cycle(
+    context: &ProjectContext,
+    build_system: BuildSystem,
+    args: &[String],
+    cycle: usize,
+    changed: &[PathBuf],
+) -> Result<(), ArkhamError> {
+    let (report, error_lines) = build_project_silent(context, build_system, args)?;
+    let mut summary = format!(
+        "[{}] #{} {} in {}, {} warning(s)",
+        Local::now().format("%H:%M:%S"),
+        cycle,
+        match report.success {
+            true => paint("PASS", Tone::Success),
+            false => paint("FAIL", Tone::Error),
+        },
+        format_duration(report.duration.as_millis() as u64),
+        report.warnings
+    );
+    if report.errors > 0 {
+        summary.push_str(&format!(", {} error(s)", report.errors));
+    }
+    if !changed.is_empty() {
+        summary.push_str(&format!(" : {}", changed_label(changed)));
+    }
+    println!("{}", summary);
+
+    for line in error_lines.iter().take(MAX_ERROR_LINES) {
+        println!("    {}", line);
+    }
+    if error_lines.len() > MAX_ERROR_LINES {
+        println!("    ... {} more", error_lines.len() - MAX_ERROR_LINES);
+    }
+    if !report.success && error_lines.is_empty() {
+        println!(
+            "    {} failed without an error line, run 'arkham build' for the full output",
+            build_system.name()
+        );
+    }
+
+    trace_event(
+        "watch_cycle",
+        &[
+            ("cycle", cycle.to_string()),
+            ("changed", changed.len().to_string()),
+            ("success", report.success.to_string()),
+        ],
+    );
+    Ok(())
+}
+
+// One build under the project lock (released before waiting for the next change)
+// --no-wait & the lock is busy => skipped, None : the next change tries again
+fn locked_cycle(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+    build_system: BuildSystem,
+    args: &[String],
+    no_wait: bool,
+    cycle: usize,
+    changed: &[PathBuf],
+) -> Result<Option<String>, ArkhamError> {
+    let _project_lock = match acquire_lock(context, "watch", no_wait) {
+        Ok(lock) => lock,
+        Err(ArkhamError::LockError(holder)) => {
+            println!(
+                "[{}] #{} SKIP: lock {}",
+                Local::now().format("%H:%M:%S"),
+                cycle,
+                holder
+            );
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+
+    build_cycle(context, build_system, args, cycle, changed)?;
+    // taken after the build : its own outputs (.o ...) don't count as a change
+    build_fingerprint(context, info, build_system, args).map(Some)
+}
+
+// arkham watch [--debounce MS] [build args] : until Ctrl-C
+pub fn watch_project(
+    context: &ProjectContext,
+    args: &[String],
+    debounce: Duration,
+    no_wait: bool,
+) -> Result<(), ArkhamError> {
+    let info = read_version_info(context)?.ok_or(ArkhamError::MissingVersionInfo)?;
+    let build_system = resolve_build_system(&context.root)?;
+    let ignore = IgnoreList::load(&context.root, &tree_artifacts(context, &info)?)?;
+
+    let (sender, events) = channel();
+    let mut watcher =
+        notify::recommended_watcher(sender).map_err(|e| ArkhamError::WatchError(e.to_string()))?;
+    watcher
+        .watch(&context.root, RecursiveMode::Recursive)
+        .map_err(|e| ArkhamError::WatchError(format!("{}: {}", context.root.display(), e)))?;
+
+    println!(
+        "Watching {} ({} {}), builds only: no version bump. Ctrl-C to stop.",
+        context.root.display(),
+        info.project_name,
+        info.current_version
+    );
+
+    let mut cycle = 1;
+    let mut last_fingerprint =
+        locked_cycle(context, &info, build_system, args, no_wait, cycle, &[])?;
+    while let Some(changed) = next_changes(&events, debounce, |event| {
+        relevant_paths(context, &info, &ignore, event)
+    }) {
+        // saved without a change (touch, a save & undo) : nothing to build
+        let fingerprint = build_fingerprint(context, &info, build_system, args)?;
+        if last_fingerprint.as_ref() == Some(&fingerprint) {
+            continue;
+        }
+        // temp files saved & renamed away (sed -i, atomic saves) : name what's still there
+        let existing: Vec<PathBuf> = changed
+            .iter()
+            .filter(|rel_path| context.root.join(rel_path).exists())
+            .cloned()
+            .collect();
+        let changed = if existing.is_empty() {
+            changed
+        } else {
+            existing
+        };
+
+        cycle += 1;
+        last_fingerprint =
+            locked_cycle(context, &info, build_system, args, no_wait, cycle, &changed)?;
+    }
+    Err(ArkhamError::WatchError(
+        "the file watcher stopped".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+    use std::sync::mpsc::Sender;
+
+    fn project() -> (tempfile::TempDir, ProjectContext, ProjectInfo) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "build/\n").unwrap();
+        let context = ProjectContext::at(dir.path());
+        let info = ProjectInfo::new(
+            "main".to_string(),
+            "1.00".to_string(),
+            dir.path().to_string_lossy().to_string(),
+        );
+        (dir, context, info)
+    }
+
+    fn modified(context: &ProjectContext, rel_path: &str) -> Event {
+        Event::new(EventKind::Modify(ModifyKind::Any)).add_path(context.path(rel_path))
+    }
+
+    #[test]
+    fn editor_noise_is_not_a_change() {
+        for noise in [
+            "main.c~",
+            ".main.c.swp",
+            ".main.c.swx",
+            ".#main.c",
+            "src/4913",
+        ] {
+            assert!(is_editor_noise(Path::new(noise)), "{}", noise);
+        }
+        for source in ["main.c", "src/swp.c", "Makefile"] {
+            assert!(!is_editor_noise(Path::new(source)), "{}", source);
+        }
+    }
+
+    #[test]
+    fn only_source_changes_are_relevant() {
+        let (_dir, context, info) = project();
+        let ignore = IgnoreList::load(&context.root, &info.artifacts).unwrap();
+        let relevant = |event| relevant_paths(&context, &info, &ignore, event);
+
+        assert_eq!(
+            relevant(modified(&context, "src/main.c")),
+            [PathBuf::from("src/main.c")]
+        );
+        let created =
+            Event::new(EventKind::Create(CreateKind::File)).add_path(context.path("util.c"));
+        assert_eq!(relevant(created), [PathBuf::from("util.c")]);
+
+        for skipped in [
+            "main",
+            "main_v_1.00",
+            "build/main.o",
+            "prev_builds/main_v_0.99",
+            ".version.info",
+            "main.c~",
+        ] {
+            assert!(
+                relevant(modified(&context, skipped)).is_empty(),
+                "{}",
+                skipped
+            );
+        }
+        let read = Event::new(EventKind::Access(AccessKind::Any)).add_path(context.path("main.c"));
+        assert!(relevant(read).is_empty());
+        let outside = Event::new(EventKind::Modify(ModifyKind::Any))
+            .add_path(PathBuf::from("/elsewhere/main.c"));
+        assert!(relevant(outside).is_empty());
+    }
+
+    #[test]
+    fn a_burst_of_saves_is_one_change() {
+        let (sender, events): (Sender<notify::Result<Event>>, _) = channel();
+        let paths = |event: Event| event.paths;
+        for name in ["a.c", "b.c", "a.c"] {
+            sender
+                .send(Ok(Event::new(EventKind::Any).add_path(PathBuf::from(name))))
+                .unwrap();
+        }
+        sender
+            .send(Err(notify::Error::generic("queue overflow")))
+            .unwrap();
+
+        let changed = next_changes(&events, Duration::from_millis(50), paths).unwrap();
+        assert_eq!(
+            changed,
+            [
+                PathBuf::from("a.c"),
+                PathBuf::from("b.c"),
+                PathBuf::from("(events lost)")
+            ]
+        );
+        assert_eq!(changed_label(&changed), "a.c, b.c (+1 more)");
+
+        drop(sender);
+        assert_eq!(
+            next_changes(&events, Duration::from_millis(50), paths),
+            None
+        );
+    }
+
+    #[test]
+    fn busy_lock_with_no_wait_skips_the_cycle() {
+        let (_dir, context, info) = project();
+        let _held = acquire_lock(&context, "build", true).unwrap();
+
+        // skipped before anything runs : no Makefile here, a build would fail
+        let cycle = locked_cycle(&context, &info, BuildSystem::Make, &[], true, 1, &[]);
+        assert_eq!(cycle.unwrap(), None);
+    }
+}
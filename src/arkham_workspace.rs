@@ -0,0 +1,299 @@
+/*
+ MONOREPO WORKSPACES : .arkham.workspace lists independently versioned components
+*/
+use crate::arkham_constants::{LOG_FIELDS, WORKSPACE_FILE};
+use crate::arkham_errors::ArkhamError;
+use crate::arkham_project::ProjectContext;
+use crate::arkham_render::fit_to_terminal;
+use crate::arkham_schema::read_log_lines;
+use crate::arkham_trace::trace_read;
+use crate::arkham_utility::display_header_msg;
+use crate::arkham_version::{parse_artifact_list, parse_log_row, read_version_info, ProjectInfo};
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use tabled::{
+    settings::{object::Segment, Alignment, Modify, Padding, Style},
+    Table, Tabled,
+};
+
+// One [component] section of the manifest
+#[derive(Debug, Clone)]
+pub struct Component {
+    pub name: String,
+    pub build_target: Option<String>,
+    pub info_file: String, // relative to the workspace root
+    pub log_file: String,
+    pub artifacts: Vec<String>, // empty => whatever .version.info says
+}
+
+impl Component {
+    // Defaults : .version.<name>.info / .version.<name>.log at the workspace root
+    fn new(name: &str) -> Self {
+        Component {
+            name: name.to_string(),
+            build_target: None,
+            info_file: format!(".version.{}.info", name),
+            log_file: format!(".version.{}.log", name),
+            artifacts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct ComponentStatus {
+    #[tabled(rename = "Component")]
+    name: String,
+    #[tabled(rename = "Version")]
+    version: String,
+    #[tabled(rename = "Build Target")]
+    target: String,
+    #[tabled(rename = "Artifacts")]
+    artifacts: String,
+    #[tabled(rename = "Last Logged")]
+    last_logged: String,
+}
+
+/*
+    # .arkham.workspace
+    [bootloader]
+    target=bootloader
+    artifacts=build/boot.bin
+    version_file=.version.bootloader.info   (optional)
+    log_file=.version.bootloader.log        (optional)
+*/
+pub fn read_workspace(root: &Path) -> Result<Vec<Component>, ArkhamError> {
+    trace_read(&root.join(WORKSPACE_FILE));
+    let manifest = fs::File::open(root.join(WORKSPACE_FILE))?;
+    let mut components: Vec<Component> = Vec::new();
+
+    for (line_num, line) in BufReader::new(manifest).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        // Skip blanks & comments
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // New component section
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let name = name.trim();
+            if name.is_empty() || components.iter().any(|c| c.name == name) {
+                return Err(ArkhamError::WorkspaceError(format!(
+                    "{} line {}: empty or duplicate component '{}'",
+                    WORKSPACE_FILE,
+                    line_num + 1,
+                    name
+                )));
+            }
+            components.push(Component::new(name));
+            continue;
+        }
+
+        let parse_info: Vec<&str> = line.splitn(2, '=').collect();
+        let component = match (components.last_mut(), parse_info.len()) {
+            (Some(component), 2) => component,
+            _ => {
+                return Err(ArkhamError::WorkspaceError(format!(
+                    "{} line {}: expected [component] or key=value inside a component",
+                    WORKSPACE_FILE,
+                    line_num + 1
+                )))
+            }
+        };
+
+        let value = parse_info[1].trim();
+        match parse_info[0].trim() {
+            "target" => component.build_target = Some(value.to_string()),
+            "version_file" => component.info_file = value.to_string(),
+            "log_file" => component.log_file = value.to_string(),
+            "artifacts" => component.artifacts = parse_artifact_list(value),
+            _ => {}
+        }
+    }
+
+    if components.is_empty() {
+        return Err(ArkhamError::WorkspaceError(format!(
+            "{} lists no components",
+            WORKSPACE_FILE
+        )));
+    }
+    Ok(components)
+}
+
+// Build outputs in the tree : info's artifacts, in a workspace every component's as well
+pub fn tree_artifacts(
+    context: &ProjectContext,
+    info: &ProjectInfo,
+) -> Result<Vec<String>, ArkhamError> {
+    let mut artifacts = info.artifacts.clone();
+    if !context.in_workspace || !context.root.join(WORKSPACE_FILE).exists() {
+        return Ok(artifacts);
+    }
+
+    for component in read_workspace(&context.root)? {
+        let component_info = read_version_info(&context.with_component(component))?;
+        for artifact in component_info
+            .map(|info| info.artifacts)
+            .unwrap_or_default()
+        {
+            if !artifacts.contains(&artifact) {
+                artifacts.push(artifact);
+            }
+        }
+    }
+    Ok(artifacts)
+}
+
+// "version (date)" of the newest log row
+fn last_logged(context: &ProjectContext) -> Result<String, ArkhamError> {
+    let log_path = context.log_file();
+    if !log_path.exists() {
+        return Ok("-".to_string());
+    }
+
+    let mut last = "-".to_string();
+    for (_, line) in read_log_lines(&log_path)?.rows {
+        let fields = parse_log_row(&line);
+        if fields.len() == LOG_FIELDS {
+            last = format!("{} ({})", fields[0], fields[2]);
+        }
+    }
+    Ok(last)
+}
+
+// arkham workspace : every component at a glance
+pub fn show_workspace_status(context: &ProjectContext) -> Result<(), ArkhamError> {
+    if !context.root.join(WORKSPACE_FILE).exists() {
+        return Err(ArkhamError::WorkspaceError(format!(
+            "No {} found in {} or above",
+            WORKSPACE_FILE,
+            context.root.display()
+        )));
+    }
+
+    let mut rows = Vec::new();
+    for component in read_workspace(&context.root)? {
+        let component_context = context.with_component(component.clone());
+
+        let (version, artifacts) = match read_version_info(&component_context)? {
+            Some(info) => (info.current_version, info.artifacts.join(", ")),
+            None => (
+                "(not initialized)".to_string(),
+                component.artifacts.join(", "),
+            ),
+        };
+
+        rows.push(ComponentStatus {
+            name: component.name.clone(),
+            version,
+            target: component.build_target.clone().unwrap_or("-".to_string()),
+            artifacts,
+            last_logged: last_logged(&component_context)?,
+        });
+    }
+
+    display_header_msg("Workspace Status");
+
+    let mut table = Table::new(rows);
+    let styled_table = table
+        .with(Style::ascii())
+        .with(Padding::new(1, 1, 0, 0))
+        .with(Modify::new(Segment::all()).with(Alignment::center()))
+        .with(fit_to_terminal());
+
+    println!("{}\n", styled_table);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arkham_version::write_version_info;
+
+    fn manifest(contents: &str) -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join(WORKSPACE_FILE), contents).unwrap();
+        root
+    }
+
+    #[test]
+    fn components_get_defaults_unless_overridden() {
+        let root = manifest(
+            "# firmware\n\
+             [kernel]\n\
+             target=kernel\n\
+             \n\
+             [bootloader]\n\
+             target = boot\n\
+             artifacts=build/boot.bin, build/boot.map\n\
+             version_file=boot/.version.info\n\
+             log_file=boot/.version.log\n",
+        );
+        let components = read_workspace(root.path()).unwrap();
+        assert_eq!(components.len(), 2);
+
+        let kernel = &components[0];
+        assert_eq!(kernel.name, "kernel");
+        assert_eq!(kernel.build_target.as_deref(), Some("kernel"));
+        assert_eq!(kernel.info_file, ".version.kernel.info");
+        assert_eq!(kernel.log_file, ".version.kernel.log");
+        assert!(kernel.artifacts.is_empty());
+
+        let bootloader = &components[1];
+        assert_eq!(bootloader.build_target.as_deref(), Some("boot"));
+        assert_eq!(bootloader.artifacts, ["build/boot.bin", "build/boot.map"]);
+        assert_eq!(bootloader.info_file, "boot/.version.info");
+        assert_eq!(bootloader.log_file, "boot/.version.log");
+    }
+
+    #[test]
+    fn malformed_manifests_are_rejected() {
+        for contents in [
+            "",
+            "# nothing but comments\n",
+            "target=kernel\n",
+            "[kernel]\n[kernel]\n",
+            "[]\n",
+            "[kernel]\njust words\n",
+        ] {
+            let root = manifest(contents);
+            assert!(
+                matches!(
+                    read_workspace(root.path()),
+                    Err(ArkhamError::WorkspaceError(_))
+                ),
+                "{:?} accepted",
+                contents
+            );
+        }
+    }
+
+    #[test]
+    fn tree_artifacts_cover_every_component() {
+        let root = manifest("[kernel]\n\n[bootloader]\nartifacts=build/boot.bin\n");
+        let components = read_workspace(root.path()).unwrap();
+        let workspace = ProjectContext::at(root.path());
+        let kernel = workspace.with_component(components[0].clone());
+        let bootloader = workspace.with_component(components[1].clone());
+
+        let root_str = root.path().to_string_lossy().to_string();
+        let kernel_info =
+            ProjectInfo::new("kernel".to_string(), "1.00".to_string(), root_str.clone());
+        write_version_info(&kernel, &kernel_info).unwrap();
+        let boot_info = ProjectInfo::new("boot".to_string(), "2.00".to_string(), root_str);
+        write_version_info(&bootloader, &boot_info).unwrap();
+
+        assert_eq!(
+            tree_artifacts(&kernel, &kernel_info).unwrap(),
+            ["kernel", "build/boot.bin"]
+        );
+        // outside a workspace : the project's own artifacts only
+        assert_eq!(
+            tree_artifacts(&ProjectContext::at(root.path()), &kernel_info).unwrap(),
+            ["kernel"]
+        );
+    }
+}
@@ -0,0 +1,77 @@
+/*
+ ARKHAM LIBRARY : versioning, logs, stamps & builds, without going through the CLI
+
+ The re-exports below are the stable API :
+    ProjectContext       where a project's state files live (discover / at)
+    ProjectInfo          .version.info          read_version_info / write_version_info
+    LogEntry             .version.log rows      read_version_log / log_version
+    BuildStats           time, size & warnings  ProjectInfo::last_build / LogEntry::stats
+    Stamp                embedded version       read_stamp / write_stamp
+    build orchestration                         build_project(_report) / update_executable_version
+ Structs & enums there are #[non_exhaustive] : they gain fields & variants in minor releases
+ (ProjectInfo::new / BuildStats::default, then set fields, to make one)
+
+ The arkham_* modules are the arkham binary's internals : the ones it calls are public but
+ #[doc(hidden)], the rest private. Neither is API, both change between releases.
+*/
+extern crate figlet_rs;
+
+// Used by the arkham binary only
+#[doc(hidden)]
+pub mod arkham_config;
+#[doc(hidden)]
+pub mod arkham_constants;
+#[doc(hidden)]
+pub mod arkham_doctor;
+#[doc(hidden)]
+pub mod arkham_errors;
+#[doc(hidden)]
+pub mod arkham_fs;
+#[doc(hidden)]
+pub mod arkham_git;
+#[doc(hidden)]
+pub mod arkham_init;
+#[doc(hidden)]
+pub mod arkham_lock;
+#[doc(hidden)]
+pub mod arkham_project;
+#[doc(hidden)]
+pub mod arkham_relocate;
+#[doc(hidden)]
+pub mod arkham_repair;
+#[doc(hidden)]
+pub mod arkham_schema;
+#[doc(hidden)]
+pub mod arkham_signing;
+#[doc(hidden)]
+pub mod arkham_stats;
+#[doc(hidden)]
+pub mod arkham_trace;
+#[doc(hidden)]
+pub mod arkham_tui;
+#[doc(hidden)]
+pub mod arkham_utility;
+#[doc(hidden)]
+pub mod arkham_version;
+#[doc(hidden)]
+pub mod arkham_watch;
+#[doc(hidden)]
+pub mod arkham_workspace;
+
+mod arkham_dryrun;
+mod arkham_editor;
+mod arkham_fingerprint;
+mod arkham_hooks;
+mod arkham_render;
+mod arkham_size;
+mod arkham_snapshot;
+
+pub use crate::arkham_config::{read_config, resolve_build_system, ArkhamConfig, BuildSystem};
+pub use crate::arkham_constants::ARKHAM_VER;
+pub use crate::arkham_errors::{validate_version, ArkhamError};
+pub use crate::arkham_project::ProjectContext;
+pub use crate::arkham_version::{
+    build_project, build_project_report, find_commit_id, log_version, read_stamp,
+    read_version_info, read_version_log, update_executable_version, update_version, write_stamp,
+    write_version_info, BuildReport, BuildStats, LogEntry, ProjectInfo, Stamp,
+};
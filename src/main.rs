@@ -1,35 +1,205 @@
-mod arkham_constants;
-mod arkham_errors;
-mod arkham_git;
-mod arkham_utility;
-mod arkham_version;
-
-extern crate figlet_rs;
-use crate::arkham_errors::ArkhamError;
-use crate::arkham_git::*;
-use crate::arkham_version::*;
-use arkham_utility::*;
+/*
+ ARKHAM CLI : thin command-line front end over the arkham library
+*/
+use arkham::arkham_doctor::run_doctor;
+use arkham::arkham_errors::ArkhamError;
+use arkham::arkham_fs::{interrupted_writes, recover_interrupted_writes};
+use arkham::arkham_git::*;
+use arkham::arkham_init::{init_project, InitOptions};
+use arkham::arkham_lock::{acquire_lock, lock_is_held};
+use arkham::arkham_project::ProjectContext;
+use arkham::arkham_relocate::{check_project_root, relocate_project};
+use arkham::arkham_repair::repair_version_log;
+use arkham::arkham_schema::{migrate_state_files, pending_migrations};
+use arkham::arkham_signing::verify_build;
+use arkham::arkham_stats::show_build_stats;
+use arkham::arkham_trace::init_tracing;
+use arkham::arkham_tui::browse_versions;
+use arkham::arkham_utility::*;
+use arkham::arkham_version::*;
+use arkham::arkham_watch::watch_project;
+use arkham::arkham_workspace::show_workspace_status;
+
+mod arkham_cli;
+
+use arkham_cli::{Cli, Command};
+use clap::error::ErrorKind;
+use clap::{ColorChoice, CommandFactory, FromArgMatches};
 use std::env;
+use std::time::Duration;
+
+// Setup failed before the command could run
+fn exit_with_error(e: &ArkhamError) -> ! {
+    println!("Error: {}", e);
+    display_error_msg("Error!");
+    std::process::exit(1);
+}
+
+// arkham help <TOPIC> : a guide topic, else the --help of that command
+fn show_help(topic: Option<&str>) {
+    match topic {
+        None => help_me(),
+        Some(topic @ ("init" | "version" | "workspace" | "git" | "hooks")) => help_with(topic),
+        Some(topic) => match Cli::command().try_get_matches_from(["arkham", topic, "--help"]) {
+            Err(e) if e.kind() == ErrorKind::DisplayHelp => {
+                let _ = e.print();
+            }
+            _ => help_with(topic),
+        },
+    }
+}
 
 // -MAIN-
 fn main() {
-    // Fetch CLI args
-    let args: Vec<String> = env::args().collect();
+    // --no-color has to reach clap before it parses : its help & errors are colored too
+    let no_color = env::args_os().any(|arg| arg == "--no-color");
 
-    let result: Result<(), ArkhamError> = match args.get(1).map(|s| s.as_str()) {
-        Some("help") => match args.get(2) {
-            Some(topic) => {
-                help_with(topic);
-                Ok(())
+    // ./arkham -DEBUG build : the old spelling of --debug
+    let args = env::args_os().map(|arg| {
+        if arg == "-DEBUG" {
+            "--debug".into()
+        } else {
+            arg
+        }
+    });
+
+    let matches = Cli::command()
+        .color(if no_color {
+            ColorChoice::Never
+        } else {
+            ColorChoice::Auto
+        })
+        .get_matches_from(args); // unknown command / bad flags : usage, "did you mean", exit 2
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // Tracing before anything touches the project (--debug / ARKHAM_DEBUG, --trace-file)
+    let debug =
+        init_tracing(cli.debug, cli.trace_file.as_deref()).unwrap_or_else(|e| exit_with_error(&e));
+    set_output_flags(cli.quiet, cli.verbose || debug, cli.no_color);
+
+    // -C / --project-dir : as if arkham was started there
+    if let Some(dir) = &cli.project_dir {
+        if let Err(e) = env::set_current_dir(dir) {
+            println!("Error: can't use {} as project dir: {}", dir.display(), e);
+            display_error_msg("Error!");
+            std::process::exit(1);
+        }
+    }
+
+    // Project root : nearest .version.info / .arkham.workspace at or above the cwd
+    let context =
+        ProjectContext::locate(cli.component.as_deref()).unwrap_or_else(|e| exit_with_error(&e));
+    debug_log(&format!("Project root: {}", context.root.display()));
+
+    let command = cli.command;
+    let mut _project_lock = None;
+    let project_command = command
+        .as_ref()
+        .filter(|cmd| !matches!(cmd, Command::Help { .. } | Command::Workspace));
+
+    // Workspace with several components : which one?
+    if project_command.is_some() && context.needs_component() {
+        println!("This is a workspace, pick a component: arkham <command> -p <component>");
+        println!("Run 'arkham workspace' to list the components.");
+        display_error_msg("Error!");
+        std::process::exit(1);
+    }
+
+    if let Some(cmd) = project_command {
+        // State-changing commands : build-bump-stamp, backup, restore, relocate & repair run one at a time
+        // & only they fix up state files : under the lock, nobody else is halfway through a write
+        if cmd.writes_state() {
+            match acquire_lock(&context, cmd.name(), cli.no_wait) {
+                Ok(lock) => _project_lock = Some(lock),
+                Err(e) => {
+                    println!("Error: {}", e);
+                    if let ArkhamError::LockError(_) = e {
+                        println!("Re-run without --no-wait to wait for it.");
+                    }
+                    display_error_msg("Error!");
+                    std::process::exit(1);
+                }
             }
-            None => {
-                help_me();
-                Ok(())
+
+            // Killed mid-write last time? interrupted update / leftover temp files / torn log record
+            if let Err(e) = recover_interrupted_writes(&context) {
+                exit_with_error(&e);
             }
-        },
-        Some("build") => {
-            let build_args: Vec<String> = args.iter().skip(2).cloned().collect(); // [cookieUFS build](ignore) V=1
-            match build_and_update(&build_args) {
+
+            // Old state file layout? upgrade it (files from a newer arkham stop us right here)
+            if let Err(e) = migrate_state_files(&context) {
+                exit_with_error(&e);
+            }
+        } else if !lock_is_held(&context) {
+            // Read-only (& --dry-run) : nothing fixed up, only pointed out
+            // (stderr : stdout may be parsed, stats --json)
+            let notes = interrupted_writes(&context)
+                .and_then(|mut notes| {
+                    notes.extend(pending_migrations(&context)?);
+                    Ok(notes)
+                })
+                .unwrap_or_else(|e| exit_with_error(&e));
+            for note in &notes {
+                eprintln!("Warning: {}", note);
+            }
+            if !notes.is_empty() {
+                eprintln!("Run 'arkham repair' to fix this up.");
+            }
+        }
+    }
+
+    // Moved / copied project? writing commands offer to fix the stored root before touching anything,
+    // read-only ones (& --dry-run) only warn : no prompt eating piped stdin
+    if let Some(cmd) = project_command {
+        if !matches!(cmd, Command::Init(_) | Command::Relocate { .. }) {
+            if let Err(e) = check_project_root(&context, cmd.writes_state()) {
+                exit_with_error(&e);
+            }
+        }
+    }
+
+    let result: Result<(), ArkhamError> = match command {
+        None => {
+            help_me();
+            Ok(())
+        }
+        Some(Command::Help { topic }) => {
+            show_help(topic.as_deref());
+            Ok(())
+        }
+        Some(Command::Init(init_args)) => {
+            // Workspace component : state files come from the manifest
+            let init_options = InitOptions {
+                context: context.component.as_ref().map(|_| context.clone()),
+                ..InitOptions::from(init_args)
+            };
+
+            match init_project(init_options) {
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    match e {
+                        ArkhamError::InvalidVersion(ref ver) => {
+                            println!("Invalid version format: {}", ver);
+                            println!("Version should be in format X.YY (e.g., 3.54)");
+                        }
+                        _ => println!("Error: {}", e),
+                    }
+                    println!("Example Usage: ");
+                    println!("  arkham init --name CookieUFS --version 3.53 --config");
+                    Err(e)
+                }
+            }
+        }
+        Some(Command::Build {
+            dry_run: true,
+            force,
+            build_args,
+        }) => plan_build(&context, &build_args, force),
+        Some(Command::Build {
+            force, build_args, ..
+        }) => {
+            // arkham build V=1 -j8 : everything after build goes to the build system
+            match build_and_update(&context, &build_args, force) {
                 Ok(_) => Ok(()),
                 Err(e) => {
                     match e {
@@ -41,42 +211,88 @@ fn main() {
                             println!("Invalid version format: {}", ver);
                             println!("Version should be in format X.YY (e.g., 3.54)");
                         }
+                        ArkhamError::HookError(ref msg) => println!("Hook Error: {}", msg),
+                        ArkhamError::SizeGateError(ref msg) => println!("Size Gate: {}", msg),
                         _ => println!("Error during *Make*: {}", e),
                     }
                     Err(e)
                 }
             }
         }
-        Some("clean") => match clean_project() {
-            Ok(_) => {
-                display_header_msg("Project cleaned successfully!");
-                Ok(())
-            }
+        Some(Command::Watch {
+            debounce,
+            build_args,
+        }) => match watch_project(
+            &context,
+            &build_args,
+            Duration::from_millis(debounce),
+            cli.no_wait,
+        ) {
+            Ok(_) => Ok(()),
             Err(e) => {
-                // println!("Clean failed: {}", e);
+                println!("Watch Error: {}", e);
                 Err(e)
             }
         },
-        Some("backup") => match save_state() {
+        Some(Command::Clean { dry_run: true }) => plan_clean(&context),
+        Some(Command::Clean { .. }) => match clean_project(&context) {
             Ok(_) => {
-                // display_header_msg("Project state saved successfully!");
+                display_success_msg("Project cleaned successfully!");
                 Ok(())
             }
             Err(e) => {
-                match e {
-                    ArkhamError::BackupError(ref msg) => {
-                        println!("Backup Error: {}", msg);
-                        println!("Failed to save project state.");
-                    }
-                    ArkhamError::IoError(ref err) => {
-                        println!("IO Error during backup: {}", err);
+                // println!("Clean failed: {}", e);
+                Err(e)
+            }
+        },
+        Some(Command::Backup {
+            dry_run: true,
+            backend,
+            ..
+        }) => plan_backup(&context, backend),
+        Some(Command::Backup {
+            backend, no_edit, ..
+        }) => {
+            // --backend overrides backup_backend from the config for this one backup
+            match save_state(&context, backend, !no_edit) {
+                Ok(_) => {
+                    // display_header_msg("Project state saved successfully!");
+                    Ok(())
+                }
+                Err(e) => {
+                    match e {
+                        ArkhamError::BackupError(ref msg) => {
+                            println!("Backup Error: {}", msg);
+                            println!("Failed to save project state.");
+                        }
+                        ArkhamError::IoError(ref err) => {
+                            println!("IO Error during backup: {}", err);
+                        }
+                        ArkhamError::HookError(ref msg) => println!("Hook Error: {}", msg),
+                        _ => println!("Unexpected error during backup: {}", e),
                     }
-                    _ => println!("Unexpected error during backup: {}", e),
+                    Err(e)
                 }
+            }
+        }
+        Some(Command::Restore { version }) => match restore_to_state(&context, &version) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!("Restore Error: {}", e);
+                Err(e)
+            }
+        },
+        Some(Command::Diff { versions }) => match diff_states(&context, &versions) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!("Error: {}", e);
+                println!("Example Usage: ");
+                println!("  arkham diff 3.51          (version vs working tree)");
+                println!("  arkham diff 3.51 3.54     (version vs version)");
                 Err(e)
             }
         },
-        Some("archives") => match show_version_logs() {
+        Some(Command::Archives) => match show_version_logs(&context) {
             Ok(_) => Ok(()),
             Err(e) => {
                 match e {
@@ -85,17 +301,15 @@ fn main() {
                         for error in errors {
                             println!("  - {}", error);
                         }
+                        println!("Run 'arkham repair' to fix or quarantine the broken rows.");
                     }
                     _ => println!("Error: {}", e),
                 }
                 Err(e)
             }
         },
-        Some("archive-entry") => {
-            // Fetch all versions from CLI
-            let versions: Vec<String> = args.iter().skip(2).cloned().collect();
-
-            match show_specific_version_logs(&versions) {
+        Some(Command::ArchiveEntry { versions }) => {
+            match show_specific_version_logs(&context, &versions) {
                 Ok(_) => Ok(()),
                 Err(e) => {
                     // Print Help if wrong command
@@ -119,14 +333,70 @@ fn main() {
                 }
             }
         }
-        Some("app-status") => current_version_info(),
-        _ => {
-            help_me();
-            Ok(())
-        }
+        Some(Command::Tui) => match browse_versions(&context) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!("Error: {}", e);
+                Err(e)
+            }
+        },
+        Some(Command::Verify {
+            binary,
+            key,
+            signature,
+            expect_version,
+        }) => match verify_build(
+            &context,
+            &binary,
+            key.as_deref(),
+            signature.as_deref(),
+            expect_version.as_deref(),
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!("Verify Error: {}", e);
+                Err(e)
+            }
+        },
+        Some(Command::AppStatus) => current_version_info(&context),
+        Some(Command::Stats { json }) => match show_build_stats(&context, json) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!("Stats Error: {}", e);
+                Err(e)
+            }
+        },
+        Some(Command::Doctor) => match run_doctor(&context) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!("Error: {}", e);
+                Err(e)
+            }
+        },
+        Some(Command::Repair) => match repair_version_log(&context) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!("Error: {}", e);
+                Err(e)
+            }
+        },
+        Some(Command::Workspace) => match show_workspace_status(&context) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!("Error: {}", e);
+                Err(e)
+            }
+        },
+        Some(Command::Relocate { from }) => match relocate_project(&context, from.as_deref()) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!("Relocate Error: {}", e);
+                Err(e)
+            }
+        },
     };
     if let Err(_err) = result {
-        display_header_msg("Error!");
+        display_error_msg("Error!");
         std::process::exit(1); // Exiting if Error Occurred, Don't want the app to panic
     }
 }